@@ -0,0 +1,75 @@
+use candid::Principal;
+use crate::state::{MAINTAINERS, ORG_MEMBERS};
+use crate::types::{Bounty, OrgRole, Permission, RecurringSeries};
+
+/// Role of `principal` in organization `org_id`, if they are a member.
+pub fn org_role(org_id: u64, principal: Principal) -> Option<OrgRole> {
+    ORG_MEMBERS.with(|members| members.borrow().get(&(org_id, principal)).map(|m| m.role))
+}
+
+fn org_role_grants(role: OrgRole, permission: Permission) -> bool {
+    match role {
+        OrgRole::Owner | OrgRole::Admin => true,
+        OrgRole::Reviewer => permission == Permission::ReviewSubmissions,
+    }
+}
+
+/// True if `caller` may exercise `permission` on `bounty`.
+/// The creator holds every permission; members of the owning organization hold what their
+/// role grants; co-maintainers hold only what they were granted.
+pub fn has_permission(bounty: &Bounty, caller: Principal, permission: Permission) -> bool {
+    if bounty.creator == caller {
+        return true;
+    }
+    if let Some(role) = bounty.org_id.and_then(|org_id| org_role(org_id, caller)) {
+        if org_role_grants(role, permission) {
+            return true;
+        }
+    }
+    MAINTAINERS.with(|maintainers| {
+        maintainers.borrow()
+            .get(&(bounty.id, caller))
+            .map(|m| m.permissions.contains(&permission))
+            .unwrap_or(false)
+    })
+}
+
+/// Single entry point for every bounty permission check in the canister.
+pub fn authorize(bounty: &Bounty, caller: Principal, permission: Permission) -> Result<(), String> {
+    if has_permission(bounty, caller, permission) {
+        Ok(())
+    } else {
+        Err(format!("Not authorized to {} on this bounty", permission.describe()))
+    }
+}
+
+/// Co-maintainers are appointed by the bounty creator, or by Owners and Admins of
+/// its organization, never by other maintainers: a grant cannot widen itself.
+pub fn authorize_maintainers(bounty: &Bounty, caller: Principal) -> Result<(), String> {
+    if bounty.creator == caller {
+        return Ok(());
+    }
+    match bounty.org_id {
+        Some(org_id) => authorize_org(org_id, caller, &[OrgRole::Owner, OrgRole::Admin]).map(|_| ()),
+        None => Err("Only the bounty creator can manage its maintainers".to_string()),
+    }
+}
+
+/// Organization-level counterpart of `authorize`: `caller` must hold one of `roles`.
+pub fn authorize_org(org_id: u64, caller: Principal, roles: &[OrgRole]) -> Result<OrgRole, String> {
+    match org_role(org_id, caller) {
+        Some(role) if roles.contains(&role) => Ok(role),
+        _ => Err("Not authorized for this organization".to_string()),
+    }
+}
+
+/// Series are managed by their creator, or by Owners and Admins of the organization they post for.
+pub fn authorize_series(series: &RecurringSeries, caller: Principal) -> Result<(), String> {
+    if series.creator == caller {
+        return Ok(());
+    }
+    match series.org_id {
+        Some(org_id) => authorize_org(org_id, caller, &[OrgRole::Owner, OrgRole::Admin]).map(|_| ()),
+        None => Err("Only the series creator can manage it".to_string()),
+    }
+}
@@ -0,0 +1,264 @@
+// Embeddable SVG badges for READMEs and issues, in the same shapes as shields.io:
+//
+//   /badge/bounty/<id>.svg?style=flat|flat-square|for-the-badge
+//   /badge/repo/<owner>/<repo>.svg?style=...   total value of the repo's active bounties
+//
+// Badges are not certified; embed them from the raw domain, see http.rs.
+use candid::{Nat, Principal};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::escrow;
+use crate::indices;
+use crate::listing;
+use crate::repos;
+use crate::state::LEDGER_SYMBOLS;
+use crate::types::{Bounty, BountyStatus};
+
+thread_local! {
+    // Ledgers that did not answer icrc1_symbol. Their badges say "tokens" rather
+    // than upgrading every request; heap only, so an upgrade retries them
+    static SYMBOL_LOOKUP_FAILED: RefCell<BTreeSet<Principal>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Style {
+    Flat,
+    FlatSquare,
+    ForTheBadge,
+}
+
+impl Style {
+    pub fn parse(s: Option<&str>) -> Result<Self, String> {
+        match s {
+            None | Some("flat") => Ok(Style::Flat),
+            Some("flat-square") => Ok(Style::FlatSquare),
+            Some("for-the-badge") => Ok(Style::ForTheBadge),
+            Some(other) => Err(format!("Invalid style: {}", other)),
+        }
+    }
+}
+
+const GREEN: &str = "#4c1";
+const YELLOW: &str = "#dfb317";
+const BLUE: &str = "#007ec6";
+const GREY: &str = "#9f9f9f";
+
+const NS_PER_MINUTE: u64 = 60 * 1_000_000_000;
+const NS_PER_HOUR: u64 = 60 * NS_PER_MINUTE;
+const NS_PER_DAY: u64 = 24 * NS_PER_HOUR;
+
+/// "💰 100 AVOX · Active · 3d left"
+pub fn bounty_badge(bounty: &Bounty, style: Style, now: u64) -> String {
+    let amount = format_amount(&bounty.prize_amount, listing::ledger_decimals(bounty.token_ledger));
+    let mut message = format!("💰 {} {} · {}", amount, symbol(bounty.token_ledger), status_label(&bounty.status));
+    if bounty.status == BountyStatus::Active {
+        if let Some(deadline) = bounty.deadline {
+            message.push_str(" · ");
+            message.push_str(&countdown(deadline, now));
+        }
+    }
+    let color = match bounty.status {
+        BountyStatus::Active => GREEN,
+        BountyStatus::PendingFunding => YELLOW,
+        BountyStatus::Completed | BountyStatus::Paid => BLUE,
+        BountyStatus::Cancelled | BountyStatus::Expired | BountyStatus::Refunded => GREY,
+    };
+    render("bounty", &message, color, style)
+}
+
+fn active_repo_bounties(owner: &str, repo: &str) -> Vec<Bounty> {
+    indices::load(repos::bounty_ids(owner, Some(repo)))
+        .into_iter()
+        .filter(|b| b.status == BountyStatus::Active)
+        .collect()
+}
+
+/// Ledgers the repository badge names.
+pub fn repo_ledgers(owner: &str, repo: &str) -> Vec<Principal> {
+    active_repo_bounties(owner, repo).iter().map(|b| b.token_ledger).collect()
+}
+
+/// Total value of the active bounties on a repository, one amount per token.
+pub fn repo_badge(owner: &str, repo: &str, style: Style) -> String {
+    let mut totals: BTreeMap<Principal, Nat> = BTreeMap::new();
+    let bounties = active_repo_bounties(owner, repo);
+    let count = bounties.len();
+    for bounty in bounties {
+        *totals.entry(bounty.token_ledger).or_insert_with(|| Nat::from(0u64)) += bounty.prize_amount;
+    }
+    if count == 0 {
+        return render("open bounties", "none", GREY, style);
+    }
+    let amounts: Vec<String> = totals
+        .iter()
+        .map(|(ledger, total)| format!("{} {}", format_amount(total, listing::ledger_decimals(*ledger)), symbol(*ledger)))
+        .collect();
+    render("open bounties", &format!("{} · 💰 {}", count, amounts.join(" + ")), GREEN, style)
+}
+
+/// Token symbol cached the first time a badge needed it, or a generic noun
+/// until then and for ledgers without one.
+pub fn symbol(token_ledger: Principal) -> String {
+    LEDGER_SYMBOLS.with(|s| s.borrow().get(&token_ledger)).unwrap_or_else(|| "tokens".to_string())
+}
+
+/// Which of `ledgers` have no cached symbol and have not failed a lookup yet.
+pub fn unknown_symbols(ledgers: impl IntoIterator<Item = Principal>) -> Vec<Principal> {
+    let ledgers: BTreeSet<Principal> = ledgers.into_iter().collect();
+    ledgers
+        .into_iter()
+        .filter(|l| LEDGER_SYMBOLS.with(|s| !s.borrow().contains_key(l)))
+        .filter(|l| SYMBOL_LOOKUP_FAILED.with(|f| !f.borrow().contains(l)))
+        .collect()
+}
+
+/// Ask each ledger for its symbol, caching the answer. Needs an update call.
+pub async fn fetch_symbols(ledgers: Vec<Principal>) {
+    for ledger in ledgers {
+        if escrow::token_symbol(ledger).await.is_err() {
+            SYMBOL_LOOKUP_FAILED.with(|f| f.borrow_mut().insert(ledger));
+        }
+    }
+}
+
+/// Whole tokens with up to four decimal places, e.g. 150_000_000 at 8 decimals -> "1.5".
+pub fn format_amount(amount: &Nat, decimals: u8) -> String {
+    let digits = amount.0.to_string();
+    let decimals = decimals as usize;
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.chars().take(4).collect::<String>();
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+fn status_label(status: &BountyStatus) -> &'static str {
+    match status {
+        BountyStatus::PendingFunding => "Awaiting funding",
+        BountyStatus::Active => "Active",
+        BountyStatus::Completed => "Winner selected",
+        BountyStatus::Cancelled => "Cancelled",
+        BountyStatus::Expired => "Expired",
+        BountyStatus::Paid => "Paid",
+        BountyStatus::Refunded => "Refunded",
+    }
+}
+
+fn countdown(deadline: u64, now: u64) -> String {
+    let remaining = deadline.saturating_sub(now);
+    if remaining == 0 {
+        "ended".to_string()
+    } else if remaining >= NS_PER_DAY {
+        format!("{}d left", remaining / NS_PER_DAY)
+    } else if remaining >= NS_PER_HOUR {
+        format!("{}h left", remaining / NS_PER_HOUR)
+    } else {
+        format!("{}m left", remaining.div_ceil(NS_PER_MINUTE))
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Rough Verdana advance widths; wide glyphs such as emoji count double
+fn text_width(text: &str, per_char: u32) -> u32 {
+    text.chars().map(|c| if c.len_utf8() > 2 { per_char * 2 } else { per_char }).sum()
+}
+
+fn render(label: &str, message: &str, color: &str, style: Style) -> String {
+    let (label, message) = match style {
+        Style::ForTheBadge => (label.to_uppercase(), message.to_uppercase()),
+        _ => (label.to_string(), message.to_string()),
+    };
+    let (height, radius, font_size, weight, baseline, per_char, padding) = match style {
+        Style::Flat => (20, 3, 11, "normal", 14, 7, 10),
+        Style::FlatSquare => (20, 0, 11, "normal", 14, 7, 10),
+        Style::ForTheBadge => (28, 0, 10, "bold", 18, 8, 24),
+    };
+    let label_width = text_width(&label, per_char) + padding;
+    let message_width = text_width(&message, per_char) + padding;
+    let width = label_width + message_width;
+    let gradient = if style == Style::Flat {
+        r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##
+    } else {
+        ""
+    };
+    let overlay = if style == Style::Flat {
+        format!(r#"<rect width="{}" height="{}" fill="url(#s)"/>"#, width, height)
+    } else {
+        String::new()
+    };
+    let (label, message) = (escape(&label), escape(&message));
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" role="img" aria-label="{label}: {message}">"#,
+            r#"<title>{label}: {message}</title>{gradient}"#,
+            r##"<clipPath id="r"><rect width="{w}" height="{h}" rx="{rx}" fill="#fff"/></clipPath>"##,
+            r##"<g clip-path="url(#r)"><rect width="{lw}" height="{h}" fill="#555"/><rect x="{lw}" width="{mw}" height="{h}" fill="{color}"/>{overlay}</g>"##,
+            r##"<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="{fs}" font-weight="{fw}">"##,
+            r#"<text x="{lx}" y="{y}">{label}</text><text x="{mx}" y="{y}">{message}</text></g></svg>"#,
+        ),
+        w = width,
+        h = height,
+        rx = radius,
+        lw = label_width,
+        mw = message_width,
+        color = color,
+        gradient = gradient,
+        overlay = overlay,
+        fs = font_size,
+        fw = weight,
+        lx = label_width / 2,
+        mx = label_width + message_width / 2,
+        y = baseline,
+        label = label,
+        message = message,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(units: u128, decimals: u8) -> String {
+        format_amount(&Nat::from(units), decimals)
+    }
+
+    #[test]
+    fn amounts_are_scaled_by_the_ledger_decimals() {
+        assert_eq!(amount(0, 8), "0");
+        assert_eq!(amount(0, 0), "0");
+        assert_eq!(amount(42, 0), "42");
+        assert_eq!(amount(100_000_000, 8), "1");
+        assert_eq!(amount(150_000_000, 8), "1.5");
+        assert_eq!(amount(1, 8), "0");
+        assert_eq!(amount(10_000, 8), "0.0001");
+        // At most four fraction digits, truncated rather than rounded
+        assert_eq!(amount(123_456_789, 8), "1.2345");
+        assert_eq!(amount(199_999_999, 8), "1.9999");
+    }
+
+    #[test]
+    fn large_amounts_keep_every_whole_digit() {
+        assert_eq!(amount(u128::MAX, 18), "340282366920938463463.3746");
+        assert_eq!(amount(u128::MAX, 0), u128::MAX.to_string());
+        assert_eq!(amount(10u128.pow(30), 18), "1000000000000");
+    }
+
+    #[test]
+    fn countdown_rounds_to_the_largest_unit() {
+        let now = 1_000 * NS_PER_DAY;
+        assert_eq!(countdown(now - 1, now), "ended");
+        assert_eq!(countdown(now, now), "ended");
+        assert_eq!(countdown(now + 1, now), "1m left");
+        assert_eq!(countdown(now + 59 * NS_PER_MINUTE, now), "59m left");
+        assert_eq!(countdown(now + NS_PER_HOUR, now), "1h left");
+        assert_eq!(countdown(now + NS_PER_DAY - 1, now), "23h left");
+        assert_eq!(countdown(now + 3 * NS_PER_DAY + 5 * NS_PER_HOUR, now), "3d left");
+    }
+}
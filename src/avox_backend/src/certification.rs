@@ -0,0 +1,134 @@
+// Certified view of bounty state.
+//
+// The canister's certified data is the root hash of a labeled tree:
+//
+//   bounty/<id>         sha256 of the candid-encoded Bounty
+//   escrow/<id>         sha256 of the escrow account text
+//   profile/<principal> sha256 of the candid-encoded UserProfile
+//   http_assets/<path>  sha256 of the JSON body served at that HTTP path
+//
+// Certified queries return the certified payload together with the IC
+// certificate and a CBOR-encoded witness for its path, so a client can check
+// the response against the subnet's public key instead of trusting the
+// boundary node that served it. The http_assets subtree follows the HTTP
+// gateway's response verification scheme, so browsers verify those paths
+// without any client code.
+use candid::Principal;
+use ic_certification::{AsHashTree, Hash, HashTree, RbTree};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use crate::escrow;
+use crate::http;
+use crate::state::{BOUNTIES, USER_PROFILES};
+use crate::types::{Bounty, UserProfile};
+
+const BOUNTY: &str = "bounty";
+const ESCROW: &str = "escrow";
+const PROFILE: &str = "profile";
+const HTTP_ASSETS: &str = "http_assets";
+
+type Tree = RbTree<&'static str, RbTree<Vec<u8>, Hash>>;
+
+thread_local! {
+    // Heap only; rebuilt from stable state by post_upgrade
+    static TREE: RefCell<Tree> = const { RefCell::new(RbTree::new()) };
+}
+
+pub fn bounty_payload(bounty: &Bounty) -> Vec<u8> {
+    candid::encode_one(bounty).unwrap_or_default()
+}
+
+pub fn profile_payload(profile: &UserProfile) -> Vec<u8> {
+    candid::encode_one(profile).unwrap_or_default()
+}
+
+pub fn escrow_payload(bounty: &Bounty) -> String {
+    escrow::get_escrow_account(bounty.escrow_subaccount).to_string()
+}
+
+/// Record the current state of a bounty and its escrow account.
+pub fn certify_bounty(bounty: &Bounty) {
+    insert_bounty(bounty);
+    publish();
+}
+
+pub fn certify_profile(principal: Principal, profile: &UserProfile) {
+    insert_profile(principal, profile);
+    publish();
+}
+
+fn insert_bounty(bounty: &Bounty) {
+    let key = bounty.id.to_string().into_bytes();
+    insert(BOUNTY, key.clone(), &bounty_payload(bounty));
+    insert(ESCROW, key, escrow_payload(bounty).as_bytes());
+    insert(HTTP_ASSETS, http::bounty_path(bounty.id).into_bytes(), &http::bounty_body(bounty));
+}
+
+fn insert_profile(principal: Principal, profile: &UserProfile) {
+    insert(PROFILE, principal.to_text().into_bytes(), &profile_payload(profile));
+    insert(HTTP_ASSETS, http::profile_path(principal).into_bytes(), &http::profile_body(principal, profile));
+}
+
+/// Rebuild the tree from stable memory, which outlives the heap across upgrades.
+pub fn rebuild() {
+    TREE.with(|tree| *tree.borrow_mut() = RbTree::new());
+    BOUNTIES.with(|bounties| {
+        for (_, bounty) in bounties.borrow().iter() {
+            insert_bounty(&bounty);
+        }
+    });
+    USER_PROFILES.with(|profiles| {
+        for (principal, profile) in profiles.borrow().iter() {
+            insert_profile(principal, &profile);
+        }
+    });
+    publish();
+}
+
+pub fn bounty_witness(bounty_id: u64) -> Result<Vec<u8>, String> {
+    witness(BOUNTY, bounty_id.to_string().as_bytes())
+}
+
+pub fn escrow_witness(bounty_id: u64) -> Result<Vec<u8>, String> {
+    witness(ESCROW, bounty_id.to_string().as_bytes())
+}
+
+pub fn profile_witness(principal: Principal) -> Result<Vec<u8>, String> {
+    witness(PROFILE, principal.to_text().as_bytes())
+}
+
+pub fn http_witness(path: &str) -> Result<Vec<u8>, String> {
+    witness(HTTP_ASSETS, path.as_bytes())
+}
+
+/// The IC certificate over the current root hash. Only available in query calls.
+pub fn certificate() -> Result<Vec<u8>, String> {
+    ic_cdk::api::data_certificate().ok_or_else(|| "Certificate is only available in query calls".to_string())
+}
+
+fn insert(label: &'static str, key: Vec<u8>, payload: &[u8]) {
+    let hash: Hash = Sha256::digest(payload).into();
+    TREE.with(|tree| {
+        let mut tree = tree.borrow_mut();
+        if tree.get(label.as_bytes()).is_none() {
+            tree.insert(label, RbTree::new());
+        }
+        tree.modify(label.as_bytes(), |subtree| subtree.insert(key, hash));
+    });
+}
+
+fn publish() {
+    let root = TREE.with(|tree| tree.borrow().root_hash());
+    ic_cdk::api::set_certified_data(&root);
+}
+
+fn witness(label: &'static str, key: &[u8]) -> Result<Vec<u8>, String> {
+    let tree: HashTree = TREE.with(|tree| {
+        tree.borrow().nested_witness(label.as_bytes(), |subtree| subtree.witness(key))
+    });
+    let mut serializer = serde_cbor::ser::Serializer::new(vec![]);
+    serializer.self_describe().map_err(|e| e.to_string())?;
+    tree.serialize(&mut serializer).map_err(|e| e.to_string())?;
+    Ok(serializer.into_inner())
+}
@@ -1,6 +1,7 @@
 use candid::{Nat, Principal};
 use ic_cdk::api::call::call;
 use sha2::{Sha256, Digest};
+use crate::state::{LEDGER_DECIMALS, LEDGER_SYMBOLS};
 
 // ICRC-1 Types
 #[derive(candid::CandidType, candid::Deserialize)]
@@ -9,8 +10,8 @@ pub struct Account {
     pub subaccount: Option<[u8; 32]>,
 }
 
-impl Account {
-    pub fn to_string(&self) -> String {
+impl std::fmt::Display for Account {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(subaccount) = self.subaccount {
             // Only show non-zero bytes of subaccount
             let non_zero_bytes: Vec<u8> = subaccount.iter()
@@ -23,12 +24,12 @@ impl Account {
                 .collect();
             
             if non_zero_bytes.is_empty() {
-                self.owner.to_string()
+                write!(f, "{}", self.owner)
             } else {
-                format!("{}-{}", self.owner, hex::encode(non_zero_bytes))
+                write!(f, "{}-{}", self.owner, hex::encode(non_zero_bytes))
             }
         } else {
-            self.owner.to_string()
+            write!(f, "{}", self.owner)
         }
     }
 }
@@ -68,6 +69,17 @@ pub fn generate_subaccount(bounty_id: u64) -> [u8; 32] {
     subaccount
 }
 
+// Generate the subaccount holding a recurring series' prepaid budget
+pub fn generate_series_subaccount(series_id: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"avox-series-");
+    hasher.update(series_id.to_be_bytes());
+    let result = hasher.finalize();
+    let mut subaccount = [0u8; 32];
+    subaccount.copy_from_slice(&result);
+    subaccount
+}
+
 // Get the escrow account for a specific bounty
 pub fn get_escrow_account(subaccount: [u8; 32]) -> Account {
     Account {
@@ -98,19 +110,70 @@ pub async fn check_balance(
     }
 }
 
+// Look up the ledger's current transfer fee
+pub async fn get_fee(token_ledger: Principal) -> Result<Nat, String> {
+    let result: Result<(Nat,), _> = call(token_ledger, "icrc1_fee", ()).await;
+    match result {
+        Ok((fee,)) => Ok(fee),
+        Err(e) => Err(format!("Failed to get fee: {:?}", e)),
+    }
+}
+
+// Look up a ledger's decimals, caching the answer since it never changes
+pub async fn token_decimals(token_ledger: Principal) -> Result<u8, String> {
+    if let Some(decimals) = LEDGER_DECIMALS.with(|d| d.borrow().get(&token_ledger)) {
+        return Ok(decimals);
+    }
+    let result: Result<(u8,), _> = call(token_ledger, "icrc1_decimals", ()).await;
+    match result {
+        Ok((decimals,)) => {
+            LEDGER_DECIMALS.with(|d| d.borrow_mut().insert(token_ledger, decimals));
+            crate::listing::resort_ledger(token_ledger, decimals);
+            Ok(decimals)
+        }
+        Err(e) => Err(format!("Failed to get token decimals: {:?}", e)),
+    }
+}
+
+// Look up a ledger's symbol, caching it for display in badges and feeds
+pub async fn token_symbol(token_ledger: Principal) -> Result<String, String> {
+    if let Some(symbol) = LEDGER_SYMBOLS.with(|s| s.borrow().get(&token_ledger)) {
+        return Ok(symbol);
+    }
+    let result: Result<(String,), _> = call(token_ledger, "icrc1_symbol", ()).await;
+    match result {
+        Ok((symbol,)) => {
+            LEDGER_SYMBOLS.with(|s| s.borrow_mut().insert(token_ledger, symbol.clone()));
+            Ok(symbol)
+        }
+        Err(e) => Err(format!("Failed to get token symbol: {:?}", e)),
+    }
+}
+
 // Transfer funds from escrow to winner
 pub async fn transfer_from_escrow(
     token_ledger: Principal,
     from_subaccount: [u8; 32],
     to: Principal,
     amount: Nat,
+) -> Result<Nat, String> {
+    let to = Account {
+        owner: to,
+        subaccount: None,
+    };
+    transfer_to_account(token_ledger, from_subaccount, to, amount).await
+}
+
+// Transfer funds from one of the canister's subaccounts to an arbitrary account
+pub async fn transfer_to_account(
+    token_ledger: Principal,
+    from_subaccount: [u8; 32],
+    to: Account,
+    amount: Nat,
 ) -> Result<Nat, String> {
     let transfer_args = TransferArg {
         from_subaccount: Some(from_subaccount),
-        to: Account {
-            owner: to,
-            subaccount: None,
-        },
+        to,
         fee: None,
         memo: None,
         created_at_time: None,
@@ -130,18 +193,15 @@ pub async fn transfer_from_escrow(
     }
 }
 
-// Helper function to create transfer instructions for users
-pub fn get_deposit_instructions(bounty_id: u64) -> String {
-    let subaccount = generate_subaccount(bounty_id);
-    let account = get_escrow_account(subaccount);
-    
-    format!(
-        "To fund this bounty, transfer tokens to:\n\
-         Account: {}\n\
-         Owner: {}\n\
-         Subaccount: {}",
-        account.to_string(),
-        ic_cdk::id(),
-        hex::encode(subaccount)
-    )
-} 
\ No newline at end of file
+// Send whatever a subaccount holds, less the transfer fee, to `to`.
+// Returns the amount sent, which is zero when the balance does not cover the fee.
+pub async fn sweep(token_ledger: Principal, from_subaccount: [u8; 32], to: Principal) -> Result<Nat, String> {
+    let fee = get_fee(token_ledger).await?;
+    let balance = check_balance(token_ledger, from_subaccount).await?;
+    if balance <= fee {
+        return Ok(Nat::from(0u64));
+    }
+    let amount = balance - fee;
+    transfer_from_escrow(token_ledger, from_subaccount, to, amount.clone()).await?;
+    Ok(amount)
+}
@@ -0,0 +1,116 @@
+use candid::Principal;
+use crate::notifications;
+use crate::state::{BOUNTY_EVENTS, EVENTS, PRINCIPAL_EVENTS};
+use crate::types::{Bounty, BountyStatus, Event, EventKind, EventPage, EventQuery};
+
+/// Append an event to the activity log and index it by bounty and principal.
+pub fn record(actor: Principal, bounty_id: Option<u64>, kind: EventKind) {
+    let mut principals = vec![actor];
+    principals.extend(named_principals(&kind));
+    principals.sort();
+    principals.dedup();
+    let event = EVENTS.with(|log| {
+        let log = log.borrow();
+        let event = Event {
+            index: log.len(),
+            timestamp: ic_cdk::api::time(),
+            actor,
+            bounty_id,
+            kind,
+        };
+        log.append(&event).expect("Failed to append event");
+        event
+    });
+    let index = event.index;
+    if let Some(bounty_id) = bounty_id {
+        BOUNTY_EVENTS.with(|idx| idx.borrow_mut().insert((bounty_id, index), ()));
+    }
+    PRINCIPAL_EVENTS.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        for principal in principals {
+            idx.insert((principal, index), ());
+        }
+    });
+    notifications::on_event(&event);
+}
+
+/// Record the event for a status change that carries no extra payload.
+/// Claims and refunds are recorded by their endpoints, which know the amount.
+pub fn record_status_change(bounty: &Bounty, actor: Principal) {
+    let kind = match (&bounty.status, bounty.winner) {
+        (BountyStatus::Active, _) => EventKind::BountyFunded,
+        (BountyStatus::Completed, Some(winner)) => EventKind::WinnerSelected { winner },
+        (BountyStatus::Cancelled, _) => EventKind::BountyCancelled,
+        (BountyStatus::Expired, _) => EventKind::BountyExpired,
+        _ => return,
+    };
+    record(actor, Some(bounty.id), kind);
+}
+
+fn named_principals(kind: &EventKind) -> Vec<Principal> {
+    match kind {
+        EventKind::BountyCreated { creator, .. } => vec![*creator],
+        EventKind::SubmissionAdded { submitter, .. } => vec![*submitter],
+        EventKind::WinnerSelected { winner } | EventKind::RewardClaimed { winner, .. } => vec![*winner],
+        EventKind::BountyRefunded { to, .. } => vec![*to],
+        EventKind::BountyFunded | EventKind::BountyCancelled | EventKind::BountyExpired | EventKind::ProfileUpdated => vec![],
+    }
+}
+
+pub fn len() -> u64 {
+    EVENTS.with(|log| log.borrow().len())
+}
+
+/// Timestamp of the most recent event, if any.
+pub fn last_timestamp() -> Option<u64> {
+    EVENTS.with(|log| {
+        let log = log.borrow();
+        log.len().checked_sub(1).and_then(|last| log.get(last)).map(|e| e.timestamp)
+    })
+}
+
+/// Up to `limit` events matching the query, starting at `query.start` inclusive.
+pub fn page(query: &EventQuery, limit: usize) -> EventPage {
+    let (lo, hi) = match (query.start, query.descending) {
+        (Some(start), false) => (start, u64::MAX),
+        (Some(start), true) => (0, start),
+        (None, _) => (0, u64::MAX),
+    };
+    let mut indices = match (query.bounty_id, query.principal) {
+        (Some(bounty_id), principal) => BOUNTY_EVENTS.with(|idx| {
+            let idx = idx.borrow();
+            let matching = idx
+                .range((bounty_id, lo)..=(bounty_id, hi))
+                .map(|((_, index), _)| index)
+                .filter(|index| principal.is_none_or(|p| involves(p, *index)));
+            take(matching, query.descending, limit + 1)
+        }),
+        (None, Some(principal)) => PRINCIPAL_EVENTS.with(|idx| {
+            let idx = idx.borrow();
+            let matching = idx.range((principal, lo)..=(principal, hi)).map(|((_, index), _)| index);
+            take(matching, query.descending, limit + 1)
+        }),
+        (None, None) => match len().checked_sub(1) {
+            Some(last) if lo <= last => take(lo..=hi.min(last), query.descending, limit + 1),
+            _ => vec![],
+        },
+    };
+    let next_index = if indices.len() > limit { indices.pop() } else { None };
+    let events = EVENTS.with(|log| {
+        let log = log.borrow();
+        indices.into_iter().filter_map(|index| log.get(index)).collect()
+    });
+    EventPage { events, next_index }
+}
+
+fn involves(principal: Principal, index: u64) -> bool {
+    PRINCIPAL_EVENTS.with(|idx| idx.borrow().contains_key(&(principal, index)))
+}
+
+fn take(indices: impl DoubleEndedIterator<Item = u64>, descending: bool, n: usize) -> Vec<u64> {
+    if descending {
+        indices.rev().take(n).collect()
+    } else {
+        indices.take(n).collect()
+    }
+}
@@ -0,0 +1,202 @@
+// Atom feeds for feed readers and chat bots:
+//
+//   /feeds/bounties.atom?ledger=&repo=<owner>/<repo>&tag=   active bounties, newest first
+//   /feeds/bounties/<id>.atom                              submissions and status changes
+//
+// Feeds are not certified; subscribe through the raw domain, see http.rs.
+use candid::Principal;
+use std::fmt::Write;
+use crate::badge::{self, escape};
+use crate::events;
+use crate::github;
+use crate::http::status_str;
+use crate::labels;
+use crate::listing;
+use crate::state::BOUNTIES;
+use crate::types::{Bounty, BountySort, BountyStatus, Event, EventKind, EventQuery};
+
+const FEED_SIZE: usize = 50;
+// Active bounties looked at per request, so a filter matching few of them
+// cannot make a query walk the whole index
+const FEED_SCAN_LIMIT: usize = 1000;
+
+pub struct BountyFeedFilter {
+    pub ledger: Option<Principal>,
+    pub repo: Option<(String, String)>,
+    pub tag: Option<String>,
+}
+
+/// The newest active bounties matching the filter, at most FEED_SIZE of them
+/// from among the FEED_SCAN_LIMIT most recently created.
+pub fn active_bounties(filter: &BountyFeedFilter, self_url: Option<&str>) -> Result<String, String> {
+    let tagged = match &filter.tag {
+        Some(tag) => labels::matching_bounty_ids(std::slice::from_ref(tag), &[], &[])?,
+        None => None,
+    };
+    let mut bounties: Vec<Bounty> = Vec::with_capacity(FEED_SIZE);
+    let mut scanned = 0;
+    BOUNTIES.with(|all| {
+        let all = all.borrow();
+        listing::walk(BountySort::CreatedAt, Some(&BountyStatus::Active), true, None, |key| {
+            scanned += 1;
+            let matches = tagged.as_ref().is_none_or(|ids| ids.contains(&key.bounty_id));
+            if let Some(bounty) = all.get(&key.bounty_id).filter(|b| matches && filter_matches(filter, b)) {
+                bounties.push(bounty);
+            }
+            bounties.len() < FEED_SIZE && scanned < FEED_SCAN_LIMIT
+        });
+    });
+
+    let updated = bounties.iter().map(funded_at).max().unwrap_or(0);
+    let mut feed = header("Avox bounties", "urn:avox:feed:bounties", updated, self_url);
+    for bounty in &bounties {
+        let amount = badge::format_amount(&bounty.prize_amount, listing::ledger_decimals(bounty.token_ledger));
+        let title = format!("{} ({} {})", bounty.title, amount, badge::symbol(bounty.token_ledger));
+        entry(&mut feed, &title, &format!("urn:avox:bounty:{}", bounty.id), funded_at(bounty), &bounty.github_issue_url, &bounty.description);
+    }
+    feed.push_str("</feed>\n");
+    Ok(feed)
+}
+
+fn filter_matches(filter: &BountyFeedFilter, bounty: &Bounty) -> bool {
+    filter.ledger.is_none_or(|l| bounty.token_ledger == l)
+        && filter.repo.as_ref().is_none_or(|r| {
+            github::parse_issue(&bounty.github_issue_url).is_ok_and(|i| (&i.owner, &i.repo) == (&r.0, &r.1))
+        })
+}
+
+/// Everything that has happened to one bounty, newest first.
+pub fn bounty_activity(bounty: &Bounty, self_url: Option<&str>) -> String {
+    let page = events::page(
+        &EventQuery { bounty_id: Some(bounty.id), principal: None, start: None, descending: true, limit: FEED_SIZE as u64 },
+        FEED_SIZE,
+    );
+    let updated = page.events.first().map(|e| e.timestamp).unwrap_or(bounty.created_at);
+    let title = format!("Bounty #{}: {}", bounty.id, bounty.title);
+    let mut feed = header(&title, &format!("urn:avox:feed:bounty:{}", bounty.id), updated, self_url);
+    for event in &page.events {
+        let (title, link) = describe(bounty, event);
+        entry(&mut feed, &title, &format!("urn:avox:event:{}", event.index), event.timestamp, &link, "");
+    }
+    feed.push_str("</feed>\n");
+    feed
+}
+
+// Bounties funded before funded_at was stored fall back to their creation time
+fn funded_at(bounty: &Bounty) -> u64 {
+    bounty.funded_at.unwrap_or(bounty.created_at)
+}
+
+fn describe(bounty: &Bounty, event: &Event) -> (String, String) {
+    let issue = bounty.github_issue_url.clone();
+    let amount = |n| format!("{} {}", badge::format_amount(n, listing::ledger_decimals(bounty.token_ledger)), badge::symbol(bounty.token_ledger));
+    match &event.kind {
+        EventKind::BountyCreated { .. } => ("Bounty created".to_string(), issue),
+        EventKind::BountyFunded => (format!("Status: {}", status_str(&BountyStatus::Active)), issue),
+        EventKind::SubmissionAdded { submission_id, submitter } => {
+            let pr_url = crate::submissions::get(bounty.id, *submission_id).map(|s| s.pr_url).unwrap_or(issue);
+            (format!("Submission #{} by {}", submission_id, submitter.to_text()), pr_url)
+        }
+        EventKind::WinnerSelected { winner } => (format!("Winner selected: {}", winner.to_text()), issue),
+        EventKind::RewardClaimed { amount: paid, .. } => (format!("Reward of {} paid out", amount(paid)), issue),
+        EventKind::BountyRefunded { amount: refunded, .. } => (format!("{} refunded to the creator", amount(refunded)), issue),
+        EventKind::BountyCancelled => (format!("Status: {}", status_str(&BountyStatus::Cancelled)), issue),
+        EventKind::BountyExpired => (format!("Status: {}", status_str(&BountyStatus::Expired)), issue),
+        EventKind::ProfileUpdated => ("Profile updated".to_string(), issue),
+    }
+}
+
+fn header(title: &str, id: &str, updated: u64, self_url: Option<&str>) -> String {
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(feed, "<title>{}</title>\n<id>{}</id>\n<updated>{}</updated>", escape(title), id, rfc3339(updated));
+    if let Some(url) = self_url {
+        let _ = writeln!(feed, "<link rel=\"self\" href=\"{}\"/>", escape(url));
+    }
+    feed
+}
+
+fn entry(feed: &mut String, title: &str, id: &str, updated: u64, link: &str, summary: &str) {
+    let _ = write!(
+        feed,
+        "<entry>\n<title>{}</title>\n<id>{}</id>\n<updated>{}</updated>\n<link href=\"{}\"/>\n",
+        escape(title),
+        id,
+        rfc3339(updated),
+        escape(link)
+    );
+    if !summary.is_empty() {
+        let _ = writeln!(feed, "<summary>{}</summary>", escape(summary));
+    }
+    feed.push_str("</entry>\n");
+}
+
+// Nanoseconds since the epoch as an RFC 3339 UTC timestamp
+fn rfc3339(ns: u64) -> String {
+    let secs = ns / 1_000_000_000;
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days, after Howard Hinnant's date algorithms
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, rem / 3_600, rem % 3_600 / 60, rem % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NS: u64 = 1_000_000_000;
+
+    #[test]
+    fn rfc3339_formats_utc_timestamps() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        // Fractions of a second are dropped
+        assert_eq!(rfc3339(NS - 1), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400 * NS), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(951_868_800 * NS), "2000-03-01T00:00:00Z");
+        assert_eq!(rfc3339(1_709_164_800 * NS + 1), "2024-02-29T00:00:00Z");
+        assert_eq!(rfc3339(1_735_689_599 * NS), "2024-12-31T23:59:59Z");
+        assert_eq!(rfc3339(4_107_542_400 * NS), "2100-03-01T00:00:00Z");
+        assert_eq!(rfc3339(u64::MAX), "2554-07-21T23:34:33Z");
+    }
+
+    fn save(bounty: &Bounty) {
+        listing::reindex(None, bounty);
+        BOUNTIES.with(|b| b.borrow_mut().insert(bounty.id, bounty.clone()));
+    }
+
+    fn entry_ids(feed: &str) -> Vec<u64> {
+        feed.match_indices("<id>urn:avox:bounty:")
+            .map(|(at, needle)| {
+                let rest = &feed[at + needle.len()..];
+                rest[..rest.find('<').unwrap()].parse().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn feed_lists_the_newest_active_bounties() {
+        for id in 1..=FEED_SIZE as u64 + 5 {
+            save(&Bounty { funded_at: Some(id * NS), ..Bounty::for_test(id, BountyStatus::Active) });
+        }
+        save(&Bounty::for_test(100, BountyStatus::PendingFunding));
+        let all = BountyFeedFilter { ledger: None, repo: None, tag: None };
+        let ids = entry_ids(&active_bounties(&all, None).unwrap());
+        assert_eq!(ids.len(), FEED_SIZE);
+        assert_eq!(ids.first(), Some(&(FEED_SIZE as u64 + 5)));
+        assert_eq!(ids.last(), Some(&6));
+
+        save(&Bounty { github_issue_url: "https://github.com/other/repo/issues/1".to_string(), ..Bounty::for_test(2, BountyStatus::Active) });
+        let other = BountyFeedFilter { ledger: None, repo: Some(("other".to_string(), "repo".to_string())), tag: None };
+        let feed = active_bounties(&other, None).unwrap();
+        assert_eq!(entry_ids(&feed), vec![2]);
+        // Bounties without funded_at fall back to their creation time
+        assert!(feed.contains("<updated>1970-01-01T00:00:00Z</updated>"));
+    }
+}
@@ -0,0 +1,460 @@
+// Helpers for the GitHub URLs bounties point at, and checks against the
+// GitHub REST API made through HTTPS outcalls
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse, TransformArgs, TransformContext,
+};
+use serde_json::{json, Value};
+use crate::types::{GithubIssueRef, PrVerification, VerificationSource};
+
+const API_BASE: &str = "https://api.github.com";
+// Pull request payloads carry the full PR body, so allow for long descriptions
+const MAX_RESPONSE_BYTES: u64 = 128 * 1024;
+// Enough for MAX_RESPONSE_BYTES on the largest subnets; unused cycles are refunded
+const OUTCALL_CYCLES: u128 = 4_000_000_000;
+const TRANSFORM_METHOD: &str = "transform_github_response";
+const PULL_CONTEXT: &[u8] = b"pull";
+const GIST_CONTEXT: &[u8] = b"gist";
+
+// Tabs of a pull request page that may follow its number
+const PULL_TABS: &[&str] = &["files", "commits", "checks"];
+
+// The one canonicalizer for numbered GitHub links, "<host>/<owner>/<repo>/<kind>/<number>".
+// Accepts http or https, an optional "www.", a trailing slash and a query or
+// fragment (e.g. "#issuecomment-1"); a pull request may also end in one of its tabs.
+fn parse_numbered(url: &str, kind: &str) -> Result<GithubIssueRef, String> {
+    let (noun, shape) = match kind {
+        "pull" => ("pull request", "Pull request URL must look like https://github.com/<owner>/<repo>/pull/<number>"),
+        _ => ("issue", "Issue URL must look like https://github.com/<owner>/<repo>/issues/<number>"),
+    };
+    let url = url.trim();
+    let lower = url.to_ascii_lowercase();
+    let rest = ["https://", "http://"]
+        .iter()
+        .find(|scheme| lower.starts_with(*scheme))
+        .map_or(url, |scheme| &url[scheme.len()..]);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/');
+    let mut parts = rest.split('/');
+    let host = parts.next().unwrap_or_default().to_ascii_lowercase();
+    if host != "github.com" && host != "www.github.com" {
+        return Err(format!("Must be a github.com {} URL", noun));
+    }
+    let parts: Vec<&str> = parts.collect();
+    let [owner, repo, found, number, tail @ ..] = parts.as_slice() else {
+        return Err(shape.to_string());
+    };
+    if kind == "issues" && *found == "pull" {
+        return Err("Link the issue the bounty is for, not a pull request".to_string());
+    }
+    let tail_ok = match tail {
+        [] => true,
+        [tab] => kind == "pull" && PULL_TABS.contains(tab),
+        _ => false,
+    };
+    if *found != kind || !tail_ok {
+        return Err(shape.to_string());
+    }
+    check_owner(owner)?;
+    check_repo(repo)?;
+    let number = number
+        .parse::<u64>()
+        .ok()
+        .filter(|n| *n > 0 && number.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| format!("Invalid {} number: {}", noun, number))?;
+    Ok(GithubIssueRef {
+        host: "github.com".to_string(),
+        owner: owner.to_lowercase(),
+        repo: repo.to_lowercase(),
+        number,
+    })
+}
+
+/// Parse an issue link into canonical form, or say why it is rejected.
+pub fn parse_issue(url: &str) -> Result<GithubIssueRef, String> {
+    parse_numbered(url, "issues")
+}
+
+/// "Owner/Repo" -> ("owner", "repo"), rejecting names GitHub would not allow.
+pub fn parse_repo(full_name: &str) -> Result<(String, String), String> {
+    let (owner, repo) = full_name.trim().split_once('/').ok_or("Repository must be given as owner/repo")?;
+    check_owner(owner)?;
+    check_repo(repo)?;
+    Ok((owner.to_lowercase(), repo.to_lowercase()))
+}
+
+pub fn check_owner(owner: &str) -> Result<(), String> {
+    let ok = (1..=39).contains(&owner.len())
+        && owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !owner.starts_with('-');
+    if ok { Ok(()) } else { Err(format!("Invalid GitHub owner: {}", owner)) }
+}
+
+fn check_repo(repo: &str) -> Result<(), String> {
+    let ok = (1..=100).contains(&repo.len())
+        && repo.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && repo != "."
+        && repo != "..";
+    if ok { Ok(()) } else { Err(format!("Invalid GitHub repository: {}", repo)) }
+}
+
+/// Parse a pull request link into the same canonical form as an issue.
+pub fn parse_pull(url: &str) -> Result<GithubIssueRef, String> {
+    parse_numbered(url, "pull")
+}
+
+/// Whether an "Owner/Repo" name from the GitHub API is the repository of `issue`.
+pub fn same_repo(full_name: &str, issue: &GithubIssueRef) -> bool {
+    parse_repo(full_name).is_ok_and(|(owner, repo)| owner == issue.owner && repo == issue.repo)
+}
+
+/// Check `pr_url` against the issue at `github_issue_url`. Problems with the PR
+/// itself are reported in `PrVerification::error`; Err means GitHub could not
+/// be reached and nothing should be recorded.
+pub async fn verify_pull(github_issue_url: &str, pr_url: &str) -> Result<PrVerification, String> {
+    let issue = parse_issue(github_issue_url).map_err(|_| "Bounty issue URL is not a github.com issue")?;
+    let checked_at = ic_cdk::api::time();
+    let Ok(pull_ref) = parse_pull(pr_url) else {
+        return Ok(PrVerification {
+            error: Some("Submission is not a github.com pull request URL".to_string()),
+            ..unverified(checked_at)
+        });
+    };
+    let url = format!("{}/repos/{}/{}/pulls/{}", API_BASE, pull_ref.owner, pull_ref.repo, pull_ref.number);
+    let (status, pull) = get_json(&url, PULL_CONTEXT).await?;
+    read_pull(&issue, status, &pull, checked_at)
+}
+
+fn unverified(checked_at: u64) -> PrVerification {
+    PrVerification {
+        checked_at,
+        merged: false,
+        closed: None,
+        same_repo: false,
+        references_issue: false,
+        author: None,
+        error: None,
+        source: Some(VerificationSource::GithubApi),
+    }
+}
+
+// Interpret a transformed pulls API response
+fn read_pull(issue: &GithubIssueRef, status: u16, pull: &Value, checked_at: u64) -> Result<PrVerification, String> {
+    let mut verification = unverified(checked_at);
+    if status == 404 {
+        verification.error = Some("Pull request not found".to_string());
+        return Ok(verification);
+    }
+    if status != 200 {
+        return Err(format!("GitHub returned status {}", status));
+    }
+    let text = format!("{}\n{}", pull["title"].as_str().unwrap_or_default(), pull["body"].as_str().unwrap_or_default());
+    verification.merged = pull["merged"].as_bool().unwrap_or(false);
+    verification.closed = pull["state"].as_str().map(|state| state == "closed");
+    verification.same_repo = pull["base_repo"].as_str().is_some_and(|r| same_repo(r, issue));
+    verification.references_issue = references_issue(&text, issue);
+    verification.author = pull["author"].as_str().map(str::to_string);
+    Ok(verification)
+}
+
+// "#12", "owner/repo#12" or the issue's URL, not followed by another digit
+pub fn references_issue(text: &str, issue: &GithubIssueRef) -> bool {
+    let text = text.to_lowercase();
+    let needles = [
+        format!("{}/{}/{}/issues/{}", issue.host, issue.owner, issue.repo, issue.number),
+        format!("{}/{}#{}", issue.owner, issue.repo, issue.number),
+        format!("#{}", issue.number),
+    ];
+    needles.iter().enumerate().any(|(i, needle)| {
+        text.match_indices(needle.as_str()).any(|(at, _)| {
+            let end = at + needle.len();
+            let next_is_digit = text[end..].chars().next().is_some_and(|c| c.is_ascii_digit());
+            // A bare "#12" must not be the tail of a reference to another repository
+            let bare_ok = i < 2 || text[..at].chars().next_back().is_none_or(|c| !c.is_alphanumeric() && c != '/' && c != '-');
+            !next_is_digit && bare_ok
+        })
+    })
+}
+
+/// Fetch an ownership proof and return the login that owns it with its text.
+/// Only public gists are accepted, https://gist.github.com/<login>/<id>: a gist
+/// can only be created by its owner, whereas a file in a repository proves no
+/// more than write access to it.
+pub async fn fetch_proof(proof_url: &str) -> Result<(String, String), String> {
+    let url = format!("{}/gists/{}", API_BASE, gist_id(proof_url)?);
+    let (status, proof) = get_json(&url, GIST_CONTEXT).await?;
+    read_proof(status, &proof)
+}
+
+fn gist_id(proof_url: &str) -> Result<&str, String> {
+    let path = proof_url.trim().strip_prefix("https://").ok_or("Proof URL must use https")?;
+    let (host, rest) = path.split_once('/').ok_or("Invalid proof URL")?;
+    if !host.eq_ignore_ascii_case("gist.github.com") {
+        return Err("Proof must be a public GitHub gist".to_string());
+    }
+    let parts: Vec<&str> = rest.split(['?', '#']).next().unwrap_or_default().split('/').filter(|p| !p.is_empty()).collect();
+    parts.last().copied().filter(|id| id.chars().all(|c| c.is_ascii_hexdigit())).ok_or_else(|| "Invalid gist URL".to_string())
+}
+
+// Interpret a transformed gists API response
+fn read_proof(status: u16, proof: &Value) -> Result<(String, String), String> {
+    if status == 404 {
+        return Err("Proof not found; it must be public".to_string());
+    }
+    if status != 200 {
+        return Err(format!("GitHub returned status {}", status));
+    }
+    let owner = proof["owner"].as_str().map(str::to_lowercase).ok_or("Could not determine who owns the proof")?;
+    Ok((owner, proof["content"].as_str().unwrap_or_default().to_string()))
+}
+
+async fn get_json(url: &str, context: &[u8]) -> Result<(u16, Value), String> {
+    let request = CanisterHttpRequestArgument {
+        url: url.to_string(),
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        method: HttpMethod::GET,
+        headers: vec![
+            HttpHeader { name: "Accept".to_string(), value: "application/vnd.github+json".to_string() },
+            HttpHeader { name: "User-Agent".to_string(), value: "avox-backend".to_string() },
+        ],
+        body: None,
+        transform: Some(TransformContext::from_name(TRANSFORM_METHOD.to_string(), context.to_vec())),
+    };
+    let (response,) = http_request(request, OUTCALL_CYCLES)
+        .await
+        .map_err(|(code, msg)| format!("GitHub request failed: {:?} {}", code, msg))?;
+    Ok(read_response(&response))
+}
+
+fn read_response(response: &HttpResponse) -> (u16, Value) {
+    let status = u16::try_from(response.status.0.clone()).unwrap_or(0);
+    (status, serde_json::from_slice(&response.body).unwrap_or(Value::Null))
+}
+
+/// Reduce a GitHub response to the fields we use, dropping headers and
+/// anything else (rate limit counters, timestamps) that differs between replicas.
+pub fn transform(args: TransformArgs) -> HttpResponse {
+    let status = args.response.status;
+    let body = if status == 200u64 {
+        let raw: Value = serde_json::from_slice(&args.response.body).unwrap_or(Value::Null);
+        let reduced = match args.context.as_slice() {
+            PULL_CONTEXT => json!({
+                "title": raw["title"],
+                "body": raw["body"],
+                "merged": raw["merged"],
+                "state": raw["state"],
+                "base_repo": raw["base"]["repo"]["full_name"],
+                "author": raw["user"]["login"],
+            }),
+            GIST_CONTEXT => {
+                let content: Vec<&str> = raw["files"]
+                    .as_object()
+                    .map(|files| files.values().filter_map(|f| f["content"].as_str()).collect())
+                    .unwrap_or_default();
+                json!({ "owner": raw["owner"]["login"], "content": content.join("\n") })
+            }
+            _ => Value::Null,
+        };
+        reduced.to_string().into_bytes()
+    } else {
+        vec![]
+    };
+    HttpResponse { status, headers: vec![], body }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Nat;
+
+    #[test]
+    fn issue_urls_share_one_canonical_form() {
+        let canonical = parse_issue("https://github.com/owner/repo/issues/12").unwrap();
+        for url in [
+            "http://github.com/owner/repo/issues/12",
+            "https://www.github.com/Owner/Repo/issues/12/",
+            "  https://GitHub.com/owner/repo/issues/12#issuecomment-1 ",
+            "https://github.com/owner/repo/issues/12?q=1",
+            "github.com/owner/repo/issues/12",
+        ] {
+            assert_eq!(parse_issue(url).as_ref(), Ok(&canonical), "{}", url);
+        }
+        assert_eq!(canonical.url(), "https://github.com/owner/repo/issues/12");
+    }
+
+    #[test]
+    fn malformed_issue_urls_are_rejected() {
+        for url in [
+            "",
+            "https://gitlab.com/owner/repo/issues/12",
+            "https://github.com/owner/repo/pull/12",
+            "https://github.com/owner/repo/issues/0",
+            "https://github.com/owner/repo/issues/+12",
+            "https://github.com/owner/repo/issues/12/files",
+            "https://github.com/-owner/repo/issues/12",
+            "https://github.com/owner/../issues/12",
+            "https://github.com/owner/repo/issues",
+        ] {
+            assert!(parse_issue(url).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn pull_urls_use_the_same_rules() {
+        let expected = GithubIssueRef { host: "github.com".to_string(), owner: "owner".to_string(), repo: "repo".to_string(), number: 7 };
+        assert_eq!(parse_pull("https://github.com/Owner/Repo/pull/7"), Ok(expected.clone()));
+        assert_eq!(parse_pull("http://www.github.com/owner/repo/pull/7/files"), Ok(expected.clone()));
+        assert!(parse_pull("https://github.com/owner/repo/issues/7").is_err());
+        assert!(parse_pull("https://github.com/owner/repo/pull/7/blame").is_err());
+        assert!(same_repo("Owner/Repo", &expected));
+        assert!(!same_repo("owner/other", &expected));
+    }
+
+    #[test]
+    fn issue_references() {
+        let issue = parse_issue("https://github.com/owner/repo/issues/12").unwrap();
+        let refs = |text: &str| references_issue(text, &issue);
+        assert!(refs("Fixes #12"));
+        assert!(refs("closes Owner/Repo#12."));
+        assert!(refs("See https://github.com/owner/repo/issues/12"));
+        assert!(!refs("Fixes #123"));
+        assert!(!refs("Fixes other/repo#12"));
+        assert!(!refs("step#12"));
+        assert!(!refs("nothing here"));
+    }
+
+    fn transformed(context: &[u8], status: u64, body: Value) -> (Nat, Value) {
+        let args = TransformArgs {
+            response: HttpResponse {
+                status: Nat::from(status),
+                headers: vec![HttpHeader { name: "x-ratelimit-remaining".to_string(), value: "59".to_string() }],
+                body: body.to_string().into_bytes(),
+            },
+            context: context.to_vec(),
+        };
+        let response = transform(args);
+        assert!(response.headers.is_empty());
+        let body = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+        (response.status, body)
+    }
+
+    #[test]
+    fn transform_keeps_only_pull_fields() {
+        let (status, body) = transformed(PULL_CONTEXT, 200, json!({
+            "title": "Fix", "body": "Fixes #1", "merged": true, "state": "closed",
+            "base": { "repo": { "full_name": "owner/repo" } },
+            "user": { "login": "dev" },
+            "updated_at": "2024-01-01T00:00:00Z",
+        }));
+        assert_eq!(status, Nat::from(200u64));
+        assert_eq!(body, json!({
+            "title": "Fix", "body": "Fixes #1", "merged": true, "state": "closed",
+            "base_repo": "owner/repo", "author": "dev",
+        }));
+    }
+
+    #[test]
+    fn transform_joins_gist_files() {
+        let (_, body) = transformed(GIST_CONTEXT, 200, json!({
+            "owner": { "login": "dev" },
+            "files": { "a.txt": { "content": "token" } },
+        }));
+        assert_eq!(body, json!({ "owner": "dev", "content": "token" }));
+    }
+
+    #[test]
+    fn transform_drops_error_bodies() {
+        let (status, body) = transformed(PULL_CONTEXT, 404, json!({ "message": "Not Found" }));
+        assert_eq!(status, Nat::from(404u64));
+        assert_eq!(body, Value::Null);
+    }
+
+    // A canned GitHub response taken through transform and decoded as get_json does
+    fn outcall(context: &[u8], status: u64, body: Value) -> (u16, Value) {
+        let args = TransformArgs {
+            response: HttpResponse { status: Nat::from(status), headers: vec![], body: body.to_string().into_bytes() },
+            context: context.to_vec(),
+        };
+        read_response(&transform(args))
+    }
+
+    fn pull(base_repo: &str, body: &str, merged: bool) -> Value {
+        json!({
+            "title": "Fix parser",
+            "body": body,
+            "merged": merged,
+            "state": if merged { "closed" } else { "open" },
+            "base": { "repo": { "full_name": base_repo } },
+            "user": { "login": "Dev" },
+            "head": { "sha": "abc123" },
+        })
+    }
+
+    #[test]
+    fn merged_pulls_are_verified_from_the_api_response() {
+        let issue = parse_issue("https://github.com/owner/repo/issues/12").unwrap();
+        let (status, body) = outcall(PULL_CONTEXT, 200, pull("Owner/Repo", "Closes #12", true));
+        let verification = read_pull(&issue, status, &body, 7).unwrap();
+        assert_eq!(verification.checked_at, 7);
+        assert!(verification.merged);
+        assert_eq!(verification.closed, Some(true));
+        assert!(verification.same_repo);
+        assert!(verification.references_issue);
+        assert_eq!(verification.author.as_deref(), Some("Dev"));
+        assert_eq!(verification.error, None);
+        assert_eq!(verification.source, Some(VerificationSource::GithubApi));
+    }
+
+    #[test]
+    fn pulls_elsewhere_or_unrelated_are_flagged() {
+        let issue = parse_issue("https://github.com/owner/repo/issues/12").unwrap();
+        let (status, body) = outcall(PULL_CONTEXT, 200, pull("fork/repo", "Closes #12", false));
+        let verification = read_pull(&issue, status, &body, 0).unwrap();
+        assert!(!verification.merged);
+        assert_eq!(verification.closed, Some(false));
+        assert!(!verification.same_repo);
+
+        let (status, body) = outcall(PULL_CONTEXT, 200, pull("owner/repo", "Unrelated cleanup", true));
+        assert!(!read_pull(&issue, status, &body, 0).unwrap().references_issue);
+    }
+
+    #[test]
+    fn pull_lookup_failures() {
+        let issue = parse_issue("https://github.com/owner/repo/issues/12").unwrap();
+        let (status, body) = outcall(PULL_CONTEXT, 404, json!({ "message": "Not Found" }));
+        let verification = read_pull(&issue, status, &body, 0).unwrap();
+        assert_eq!(verification.error.as_deref(), Some("Pull request not found"));
+        assert!(!verification.merged && !verification.same_repo);
+
+        // Rate limits and outages are not recorded against the submission
+        let (status, body) = outcall(PULL_CONTEXT, 403, json!({ "message": "API rate limit exceeded" }));
+        assert_eq!(read_pull(&issue, status, &body, 0).unwrap_err(), "GitHub returned status 403");
+    }
+
+    #[test]
+    fn gist_proofs_are_read_from_the_api_response() {
+        let (status, body) = outcall(GIST_CONTEXT, 200, json!({
+            "owner": { "login": "Dev" },
+            "files": { "proof.txt": { "content": "avox:token" } },
+            "history": [{ "version": "1" }],
+        }));
+        assert_eq!(read_proof(status, &body).unwrap(), ("dev".to_string(), "avox:token".to_string()));
+
+        let (status, body) = outcall(GIST_CONTEXT, 200, json!({ "files": {} }));
+        assert!(read_proof(status, &body).is_err());
+        let (status, body) = outcall(GIST_CONTEXT, 404, json!({ "message": "Not Found" }));
+        assert_eq!(read_proof(status, &body).unwrap_err(), "Proof not found; it must be public");
+    }
+
+    #[test]
+    fn proofs_must_be_gist_urls() {
+        assert_eq!(gist_id("https://gist.github.com/dev/aa01ff"), Ok("aa01ff"));
+        assert_eq!(gist_id(" https://Gist.GitHub.com/dev/aa01ff/?x=1#f "), Ok("aa01ff"));
+        for url in [
+            "http://gist.github.com/dev/aa01ff",
+            "https://github.com/dev/repo/blob/main/proof.txt",
+            "https://gist.github.com/dev/not-hex",
+            "https://gist.github.com",
+        ] {
+            assert!(gist_id(url).is_err(), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,647 @@
+// Read-only JSON API served through the HTTP gateway.
+//
+//   GET /api/status
+//   GET /api/bounties?status=&sort=&direction=&cursor=&limit=
+//   GET /api/bounties?tag=&language=&skill=&max_hours=&status=&offset=&limit=
+//   GET /api/bounties?q=&cursor=                       (ranked search, same filters)
+//   GET /api/bounties/<id>                             (certified)
+//   GET /api/bounties/<id>/submissions?start_after=&limit=
+//   GET /api/users/<principal>                         (certified)
+//   GET /api/users/<principal>/bounties
+//   GET /api/leaderboard/<creators|winners|participants>?limit=&verified=true
+//   GET /api/repos/<owner>/<repo>?offset=&limit=       (stats and a page of open bounties)
+//   GET /api/owners/<owner>                            (stats across the owner's repositories)
+//   GET /badge/...                                     (SVG, see badge.rs)
+//   GET /feeds/...                                     (Atom, see feed.rs)
+//   POST /webhooks/github                              (upgraded, see webhook.rs)
+//
+// Amounts are decimal strings and principals are textual, so any JSON client
+// can read them without knowing Candid.
+//
+// Only the paths marked certified carry an IC-Certificate header, and only on
+// GET. Everything else, lists, badges and feeds included, is uncertified and
+// fails response verification on the certified gateway domain
+// (<canister>.icp0.io); link to it through the raw domain instead:
+// https://<canister>.raw.icp0.io/badge/bounty/1.svg
+use base64::Engine;
+use candid::{Nat, Principal};
+use serde_json::{json, Value};
+use crate::badge::{self, Style};
+use crate::certification;
+use crate::feed::{self, BountyFeedFilter};
+use crate::github;
+use crate::state::{BOUNTIES, USER_PROFILES};
+use crate::types::*;
+use crate::webhook;
+
+const DEFAULT_LIMIT: u64 = 20;
+const CACHE_RECORD: &str = "public, max-age=10";
+const CACHE_LIST: &str = "public, max-age=30";
+const CACHE_BADGE: &str = "public, max-age=300";
+const CACHE_FEED: &str = "public, max-age=300";
+const ATOM: &str = "application/atom+xml; charset=utf-8";
+
+pub struct Request {
+    pub segments: Vec<String>,
+    pub params: Vec<(String, String)>,
+    // The path exactly as sent, which is what the HTTP gateway verifies against
+    raw_path: String,
+    query: String,
+}
+
+impl Request {
+    pub fn parse(url: &str) -> Self {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments = path.split('/').filter(|s| !s.is_empty()).map(decode_component).collect();
+        let params = query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let (k, v) = p.split_once('=').unwrap_or((p, ""));
+                (decode_component(k), decode_component(v))
+            })
+            .collect();
+        Request { segments, params, raw_path: path.to_string(), query: query.to_string() }
+    }
+
+    pub fn path(&self) -> String {
+        format!("/{}", self.segments.join("/"))
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// All values of a repeatable parameter; `tag=a&tag=b` and `tag=a,b` are equivalent.
+    pub fn param_list(&self, name: &str) -> Vec<String> {
+        self.params
+            .iter()
+            .filter(|(k, _)| k == name)
+            .flat_map(|(_, v)| v.split(','))
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn param_u64(&self, name: &str) -> Result<Option<u64>, String> {
+        self.param(name)
+            .map(|v| v.parse().map_err(|_| format!("Invalid {}: {}", name, v)))
+            .transpose()
+    }
+
+    fn limit(&self) -> Result<u64, String> {
+        Ok(self.param_u64("limit")?.unwrap_or(DEFAULT_LIMIT).clamp(1, crate::MAX_PAGE_SIZE))
+    }
+}
+
+fn decode_component(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(hi), Some(lo)) => {
+                        out.push(hi << 4 | lo);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+const WEBHOOK_PATH: &str = "/webhooks/github";
+
+pub fn route(request: &HttpRequest) -> HttpResponse {
+    if request.method == "POST" && Request::parse(&request.url).path() == WEBHOOK_PATH {
+        // Deliveries change state, so ask the gateway to replay them as an update
+        let mut response = response(200, "text/plain; charset=utf-8", "no-store", vec![]);
+        response.upgrade = Some(true);
+        return response;
+    }
+    if request.method != "GET" && request.method != "HEAD" {
+        return error(405, "Only GET is supported");
+    }
+    let req = Request::parse(&request.url);
+    let segments: Vec<&str> = req.segments.iter().map(String::as_str).collect();
+    if request.method == "GET" && !badge_ledgers(&segments).is_empty() {
+        // Symbols are looked up once per ledger, which needs an update call
+        let mut response = response(200, "text/plain; charset=utf-8", "no-store", vec![]);
+        response.upgrade = Some(true);
+        return response;
+    }
+    let self_url = request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("host"))
+        .map(|(_, host)| format!("https://{}{}", host, request.url));
+    let result = match segments.as_slice() {
+        ["api", "status"] => Ok(status()),
+        ["api", "bounties"] => list_bounties(&req),
+        ["api", "bounties", id] => get_bounty(&req, id),
+        ["api", "bounties", id, "submissions"] => list_submissions(&req, id),
+        ["api", "users", user] => get_profile(&req, user),
+        ["api", "users", user, "bounties"] => list_user_bounties(user),
+        ["api", "leaderboard", board] => leaderboard(&req, board),
+        ["api", "repos", owner, repo] => repo_stats(&req, owner, repo),
+        ["api", "owners", owner] => owner_stats(owner),
+        ["badge", "bounty", file] => bounty_badge(&req, file),
+        ["badge", "repo", owner, file] => repo_badge(&req, owner, file),
+        ["feeds", "bounties.atom"] => bounties_feed(&req, self_url.as_deref()),
+        ["feeds", "bounties", file] => bounty_feed(file, self_url.as_deref()),
+        _ => Err((404, "Not found".to_string())),
+    };
+    let mut response = result.unwrap_or_else(|(code, message)| error(code, &message));
+    if request.method == "HEAD" {
+        // The certificate covers the full body, so it cannot verify an empty one
+        response.body.clear();
+        response.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("IC-Certificate"));
+    }
+    response
+}
+
+/// Requests the gateway replays through http_request_update.
+pub async fn route_update(request: &HttpRequest) -> HttpResponse {
+    let req = Request::parse(&request.url);
+    if request.method == "POST" && req.path() == WEBHOOK_PATH {
+        return webhook::handle(request);
+    }
+    let segments: Vec<&str> = req.segments.iter().map(String::as_str).collect();
+    let ledgers = badge_ledgers(&segments);
+    if request.method == "GET" && !ledgers.is_empty() {
+        badge::fetch_symbols(ledgers).await;
+        return route(request);
+    }
+    error(404, "Not found")
+}
+
+// Ledgers a badge at this path would name whose symbol is not known yet
+fn badge_ledgers(segments: &[&str]) -> Vec<Principal> {
+    let ledgers = match segments {
+        ["badge", "bounty", file] => parse_id(file.strip_suffix(".svg").unwrap_or(file))
+            .ok()
+            .and_then(|id| BOUNTIES.with(|b| b.borrow().get(&id)))
+            .map(|b| vec![b.token_ledger])
+            .unwrap_or_default(),
+        ["badge", "repo", owner, file] => badge_repo(owner, file)
+            .map(|(owner, repo)| badge::repo_ledgers(&owner, &repo))
+            .unwrap_or_default(),
+        _ => vec![],
+    };
+    badge::unknown_symbols(ledgers)
+}
+
+type RouteResult = Result<HttpResponse, (u16, String)>;
+
+fn bad_request(message: String) -> (u16, String) {
+    (400, message)
+}
+
+fn parse_id(id: &str) -> Result<u64, (u16, String)> {
+    id.parse().map_err(|_| (404, "Bounty not found".to_string()))
+}
+
+fn parse_principal(text: &str) -> Result<Principal, (u16, String)> {
+    Principal::from_text(text).map_err(|_| (404, "User not found".to_string()))
+}
+
+fn list_bounties(req: &Request) -> RouteResult {
+    let status = req.param("status").map(parse_status).transpose().map_err(bad_request)?;
+    let limit = req.limit().map_err(bad_request)?;
+    let filter = BountyFilter {
+        status: status.clone(),
+        tags: req.param_list("tag"),
+        languages: req.param_list("language"),
+        skills: req.param_list("skill"),
+        max_estimated_hours: req.param_u64("max_hours").map_err(bad_request)?.map(|h| h.min(u32::MAX as u64) as u32),
+    };
+    if let Some(q) = req.param("q") {
+        let page = crate::search_bounties(q.to_string(), filter, req.param("cursor").map(str::to_string)).map_err(bad_request)?;
+        let hits: Vec<Value> = page.hits.iter().map(|h| json!({ "bounty": summary_json(&h.bounty), "score": h.score })).collect();
+        return Ok(json_response(json!({ "hits": hits, "total_matches": page.total_matches, "next_cursor": page.next_cursor }), CACHE_LIST));
+    }
+    let labelled = !filter.tags.is_empty() || !filter.languages.is_empty() || !filter.skills.is_empty() || filter.max_estimated_hours.is_some();
+    if labelled || req.param("offset").is_some() {
+        let offset = req.param_u64("offset").map_err(bad_request)?.unwrap_or(0);
+        let bounties = crate::get_bounty_summaries_filtered(filter, offset, limit).map_err(bad_request)?;
+        let next_offset = if bounties.len() as u64 == limit { Some(offset + limit) } else { None };
+        let bounties: Vec<Value> = bounties.iter().map(summary_json).collect();
+        return Ok(json_response(json!({ "bounties": bounties, "next_offset": next_offset }), CACHE_LIST));
+    }
+    let query = BountyListQuery {
+        sort: req.param("sort").map(parse_sort).transpose().map_err(bad_request)?.unwrap_or(BountySort::CreatedAt),
+        direction: match req.param("direction") {
+            None | Some("desc") => SortDirection::Descending,
+            Some("asc") => SortDirection::Ascending,
+            Some(other) => return Err(bad_request(format!("Invalid direction: {}", other))),
+        },
+        status,
+        cursor: req.param("cursor").map(str::to_string),
+        limit,
+    };
+    let page = crate::list_bounty_summaries(query).map_err(bad_request)?;
+    let bounties: Vec<Value> = page.bounties.iter().map(summary_json).collect();
+    Ok(json_response(json!({ "bounties": bounties, "next_cursor": page.next_cursor }), CACHE_LIST))
+}
+
+fn status() -> HttpResponse {
+    let status = crate::get_status();
+    json_response(json!({
+        "version": status.version,
+        "bounty_count": status.bounty_count,
+        "last_updated": status.last_updated,
+    }), CACHE_LIST)
+}
+
+fn get_bounty(req: &Request, id: &str) -> RouteResult {
+    let bounty_id = parse_id(id)?;
+    let bounty = BOUNTIES.with(|b| b.borrow().get(&bounty_id)).ok_or((404, "Bounty not found".to_string()))?;
+    Ok(certified_response(req, &bounty_path(bounty_id), bounty_body(&bounty), CACHE_RECORD))
+}
+
+fn list_submissions(req: &Request, id: &str) -> RouteResult {
+    let bounty_id = parse_id(id)?;
+    if !BOUNTIES.with(|b| b.borrow().contains_key(&bounty_id)) {
+        return Err((404, "Bounty not found".to_string()));
+    }
+    let start_after = req.param_u64("start_after").map_err(bad_request)?;
+    let page = crate::get_bounty_submissions(bounty_id, start_after, req.limit().map_err(bad_request)?);
+    let submissions: Vec<Value> = page.submissions.iter().map(submission_json).collect();
+    Ok(json_response(json!({ "submissions": submissions, "next_cursor": page.next_cursor }), CACHE_LIST))
+}
+
+fn get_profile(req: &Request, user: &str) -> RouteResult {
+    let principal = parse_principal(user)?;
+    let profile = USER_PROFILES.with(|p| p.borrow().get(&principal)).ok_or((404, "User not found".to_string()))?;
+    Ok(certified_response(req, &profile_path(principal), profile_body(principal, &profile), CACHE_RECORD))
+}
+
+fn list_user_bounties(user: &str) -> RouteResult {
+    let principal = parse_principal(user)?;
+    let bounties: Vec<Value> = crate::get_user_created_bounty_summaries(principal).iter().map(summary_json).collect();
+    Ok(json_response(json!({ "bounties": bounties }), CACHE_LIST))
+}
+
+fn leaderboard(req: &Request, board: &str) -> RouteResult {
+    let metric: fn(&UserProfile) -> u64 = match board {
+        "creators" => |p| p.bounties_posted,
+        "winners" => |p| p.bounties_won,
+        "participants" => |p| p.bounties_participated,
+        _ => return Err((404, "Not found".to_string())),
+    };
+    let limit = req.limit().map_err(bad_request)?;
+    let verified_only = req.param("verified") == Some("true");
+    let entries: Vec<Value> = crate::top_profiles(metric, limit, verified_only)
+        .iter()
+        .map(|(principal, p)| profile_json(*principal, p))
+        .collect();
+    Ok(json_response(json!({ "entries": entries }), CACHE_LIST))
+}
+
+fn bounty_badge(req: &Request, file: &str) -> RouteResult {
+    let bounty_id = parse_id(file.strip_suffix(".svg").unwrap_or(file))?;
+    let style = Style::parse(req.param("style")).map_err(bad_request)?;
+    let bounty = BOUNTIES.with(|b| b.borrow().get(&bounty_id)).ok_or((404, "Bounty not found".to_string()))?;
+    let svg = badge::bounty_badge(&bounty, style, ic_cdk::api::time());
+    Ok(response(200, "image/svg+xml", CACHE_BADGE, svg.into_bytes()))
+}
+
+// Stats plus one page of the open bounties; open_count says how many there are
+fn repo_stats(req: &Request, owner: &str, repo: &str) -> RouteResult {
+    let stats = crate::get_repo_stats(format!("{}/{}", owner, repo), None).map_err(bad_request)?;
+    let offset = req.param_u64("offset").map_err(bad_request)?.unwrap_or(0);
+    let limit = req.limit().map_err(bad_request)?;
+    let open = crate::get_repo_bounties(format!("{}/{}", owner, repo), true, offset, limit).map_err(bad_request)?;
+    let next_offset = if open.len() as u64 == limit { Some(offset + limit) } else { None };
+    let mut body = stats_json(&stats);
+    body["open_bounties"] = Value::Array(open.iter().map(summary_json).collect());
+    body["next_offset"] = json!(next_offset);
+    Ok(json_response(body, CACHE_LIST))
+}
+
+fn owner_stats(owner: &str) -> RouteResult {
+    let stats = crate::get_owner_stats(owner.to_string(), None).map_err(bad_request)?;
+    let mut body = stats_json(&stats.total);
+    body["repos"] = Value::Array(stats.repos.iter().map(stats_json).collect());
+    Ok(json_response(body, CACHE_LIST))
+}
+
+fn stats_json(stats: &RepoStats) -> Value {
+    let amounts = |amounts: &[TokenAmount]| -> Vec<Value> {
+        amounts
+            .iter()
+            .map(|a| json!({ "token_ledger": a.token_ledger.to_text(), "amount": nat_str(&a.amount) }))
+            .collect()
+    };
+    json!({
+        "owner": stats.owner,
+        "repo": stats.repo,
+        "bounty_count": stats.bounty_count,
+        "open_count": stats.open_count,
+        "completed_count": stats.completed_count,
+        "unawarded_count": stats.unawarded_count,
+        "completion_rate": stats.completion_rate,
+        "value_locked": amounts(&stats.value_locked),
+        "top_contributors": stats.top_contributors.iter().map(|c| json!({
+            "principal": c.principal.to_text(),
+            "bounties_won": c.bounties_won,
+            "amount_won": amounts(&c.amount_won),
+        })).collect::<Vec<Value>>(),
+    })
+}
+
+// "/badge/repo/<owner>/<repo>.svg" -> ("owner", "repo")
+fn badge_repo(owner: &str, file: &str) -> Result<(String, String), String> {
+    github::parse_repo(&format!("{}/{}", owner, file.strip_suffix(".svg").unwrap_or(file)))
+}
+
+fn repo_badge(req: &Request, owner: &str, file: &str) -> RouteResult {
+    let (owner, repo) = badge_repo(owner, file).map_err(bad_request)?;
+    let style = Style::parse(req.param("style")).map_err(bad_request)?;
+    let svg = badge::repo_badge(&owner, &repo, style);
+    Ok(response(200, "image/svg+xml", CACHE_BADGE, svg.into_bytes()))
+}
+
+fn bounties_feed(req: &Request, self_url: Option<&str>) -> RouteResult {
+    let ledger = req
+        .param("ledger")
+        .map(|l| Principal::from_text(l).map_err(|_| format!("Invalid ledger: {}", l)))
+        .transpose()
+        .map_err(bad_request)?;
+    let repo = req
+        .param("repo")
+        .map(github::parse_repo)
+        .transpose()
+        .map_err(bad_request)?;
+    let filter = BountyFeedFilter { ledger, repo, tag: req.param("tag").map(str::to_string) };
+    let atom = feed::active_bounties(&filter, self_url).map_err(bad_request)?;
+    Ok(response(200, ATOM, CACHE_FEED, atom.into_bytes()))
+}
+
+fn bounty_feed(file: &str, self_url: Option<&str>) -> RouteResult {
+    let bounty_id = parse_id(file.strip_suffix(".atom").unwrap_or(file))?;
+    let bounty = BOUNTIES.with(|b| b.borrow().get(&bounty_id)).ok_or((404, "Bounty not found".to_string()))?;
+    Ok(response(200, ATOM, CACHE_FEED, feed::bounty_activity(&bounty, self_url).into_bytes()))
+}
+
+pub fn bounty_path(bounty_id: u64) -> String {
+    format!("/api/bounties/{}", bounty_id)
+}
+
+pub fn profile_path(principal: Principal) -> String {
+    format!("/api/users/{}", principal.to_text())
+}
+
+/// JSON body served at `bounty_path`. Certified, so it may only depend on the
+/// bounty record itself.
+pub fn bounty_body(bounty: &Bounty) -> Vec<u8> {
+    json!({
+        "id": bounty.id,
+        "creator": bounty.creator.to_text(),
+        "title": bounty.title,
+        "description": bounty.description,
+        "github_issue_url": bounty.github_issue_url,
+        "prize_amount": nat_str(&bounty.prize_amount),
+        "token_ledger": bounty.token_ledger.to_text(),
+        "status": status_str(&bounty.status),
+        "winner": bounty.winner.map(|w| w.to_text()),
+        "created_at": bounty.created_at,
+        "funded_at": bounty.funded_at,
+        "deadline": bounty.deadline,
+        "escrow_account": certification::escrow_payload(bounty),
+        "competition_mode": mode_str(&bounty.competition_mode()),
+        "org_id": bounty.org_id,
+        "series_id": bounty.series_id,
+        "issue_closed_at": bounty.issue_closed_at,
+        "labels": bounty.labels.as_ref().map(|l| json!({
+            "tags": l.tags,
+            "languages": l.languages,
+            "skills": l.skills,
+            "estimated_hours": l.estimated_hours,
+        })),
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// JSON body served at `profile_path`. Certified like `bounty_body`.
+pub fn profile_body(principal: Principal, profile: &UserProfile) -> Vec<u8> {
+    profile_json(principal, profile).to_string().into_bytes()
+}
+
+fn profile_json(principal: Principal, profile: &UserProfile) -> Value {
+    json!({
+        "principal": principal.to_text(),
+        "name": profile.name,
+        "github": profile.github,
+        "github_verified": profile.github_verified.as_ref().map(|v| v.login.clone()),
+        "twitter": profile.twitter,
+        "pfp_url": profile.pfp_url,
+        "bounties_posted": profile.bounties_posted,
+        "bounties_participated": profile.bounties_participated,
+        "bounties_won": profile.bounties_won,
+    })
+}
+
+fn summary_json(summary: &BountySummary) -> Value {
+    json!({
+        "id": summary.id,
+        "title": summary.title,
+        "status": status_str(&summary.status),
+        "prize_amount": nat_str(&summary.prize_amount),
+        "token_ledger": summary.token_ledger.to_text(),
+        "deadline": summary.deadline,
+        "submission_count": summary.submission_count,
+        "creator": summary.creator.to_text(),
+    })
+}
+
+fn submission_json(submission: &Submission) -> Value {
+    json!({
+        "id": submission.id,
+        "bounty_id": submission.bounty_id,
+        "submitter": submission.submitter.to_text(),
+        "pr_url": submission.pr_url,
+        "comment": submission.comment,
+        "submitted_at": submission.submitted_at,
+        "pr": submission.verification.as_ref().map(|v| json!({
+            "merged": v.merged,
+            "closed": v.closed,
+            "checked_at": v.checked_at,
+            "source": match v.source {
+                Some(VerificationSource::Webhook) => "webhook",
+                _ => "github_api",
+            },
+        })),
+    })
+}
+
+pub fn nat_str(n: &Nat) -> String {
+    n.0.to_string()
+}
+
+pub fn status_str(status: &BountyStatus) -> &'static str {
+    match status {
+        BountyStatus::PendingFunding => "pending_funding",
+        BountyStatus::Active => "active",
+        BountyStatus::Completed => "completed",
+        BountyStatus::Cancelled => "cancelled",
+        BountyStatus::Expired => "expired",
+        BountyStatus::Paid => "paid",
+        BountyStatus::Refunded => "refunded",
+    }
+}
+
+fn parse_status(s: &str) -> Result<BountyStatus, String> {
+    [
+        BountyStatus::PendingFunding,
+        BountyStatus::Active,
+        BountyStatus::Completed,
+        BountyStatus::Cancelled,
+        BountyStatus::Expired,
+        BountyStatus::Paid,
+        BountyStatus::Refunded,
+    ]
+    .into_iter()
+    .find(|status| status_str(status) == s)
+    .ok_or_else(|| format!("Invalid status: {}", s))
+}
+
+fn parse_sort(s: &str) -> Result<BountySort, String> {
+    match s {
+        "created_at" => Ok(BountySort::CreatedAt),
+        "prize" => Ok(BountySort::PrizeAmount),
+        "deadline" => Ok(BountySort::Deadline),
+        "submissions" => Ok(BountySort::Submissions),
+        _ => Err(format!("Invalid sort: {}", s)),
+    }
+}
+
+fn mode_str(mode: &CompetitionMode) -> &'static str {
+    match mode {
+        CompetitionMode::Open => "open",
+        CompetitionMode::Exclusive => "exclusive",
+        CompetitionMode::Application => "application",
+    }
+}
+
+pub fn response(status_code: u16, content_type: &str, cache: &str, body: Vec<u8>) -> HttpResponse {
+    HttpResponse {
+        status_code,
+        headers: vec![
+            ("Content-Type".to_string(), content_type.to_string()),
+            ("Cache-Control".to_string(), cache.to_string()),
+            ("Access-Control-Allow-Origin".to_string(), "*".to_string()),
+        ],
+        body,
+        upgrade: None,
+    }
+}
+
+fn json_response(value: Value, cache: &str) -> HttpResponse {
+    response(200, "application/json; charset=utf-8", cache, value.to_string().into_bytes())
+}
+
+pub fn error(status_code: u16, message: &str) -> HttpResponse {
+    let body = json!({ "error": message }).to_string().into_bytes();
+    response(status_code, "application/json; charset=utf-8", "no-store", body)
+}
+
+// Serve a body whose hash is in the certification tree under its canonical
+// path, with an IC-Certificate header the HTTP gateway can verify. The gateway
+// verifies against the raw request path, and the tree only holds canonical
+// paths, so any other spelling of the path (a trailing slash, percent-encoding,
+// a padded id) is redirected to the canonical one instead of served uncertifiable
+fn certified_response(req: &Request, path: &str, body: Vec<u8>, cache: &str) -> HttpResponse {
+    if req.raw_path != path {
+        let location = if req.query.is_empty() { path.to_string() } else { format!("{}?{}", path, req.query) };
+        let mut response = response(308, "text/plain; charset=utf-8", CACHE_RECORD, Vec::new());
+        response.headers.push(("Location".to_string(), location));
+        return response;
+    }
+    let mut response = response(200, "application/json; charset=utf-8", cache, body);
+    if let (Ok(certificate), Ok(tree)) = (certification::certificate(), certification::http_witness(path)) {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        response.headers.push((
+            "IC-Certificate".to_string(),
+            format!("certificate=:{}:, tree=:{}:", b64.encode(certificate), b64.encode(tree)),
+        ));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components_are_percent_decoded() {
+        assert_eq!(decode_component("rust%20lang"), "rust lang");
+        assert_eq!(decode_component("rust+lang"), "rust lang");
+        assert_eq!(decode_component("%2B%2f"), "+/");
+        assert_eq!(decode_component("%41"), "A");
+        assert_eq!(decode_component("caf%C3%A9"), "café");
+    }
+
+    #[test]
+    fn bad_escapes_are_kept_literally() {
+        assert_eq!(decode_component("%"), "%");
+        assert_eq!(decode_component("a%4"), "a%4");
+        assert_eq!(decode_component("%zz1"), "%zz1");
+        assert_eq!(decode_component("%4g"), "%4g");
+        assert_eq!(decode_component("%ff"), "\u{fffd}");
+    }
+
+    #[test]
+    fn paths_are_split_into_decoded_segments() {
+        let req = Request::parse("/api//bounties/7/");
+        assert_eq!(req.segments, ["api", "bounties", "7"]);
+        assert_eq!(req.path(), "/api/bounties/7");
+        assert_eq!(req.raw_path, "/api//bounties/7/");
+
+        let req = Request::parse("/api/repos/owner%2Frepo");
+        assert_eq!(req.segments, ["api", "repos", "owner/repo"]);
+        assert_eq!(Request::parse("").path(), "/");
+    }
+
+    #[test]
+    fn dot_segments_are_not_resolved() {
+        let req = Request::parse("/api/bounties/../users/aaaaa-aa");
+        assert_eq!(req.segments, ["api", "bounties", "..", "users", "aaaaa-aa"]);
+        assert!(parse_id("..").is_err());
+    }
+
+    #[test]
+    fn query_strings_are_parsed() {
+        let req = Request::parse("/api/bounties?status=active&tag=a,b&&tag=c&flag&q=a%26b+c&limit=x");
+        assert_eq!(req.segments, ["api", "bounties"]);
+        assert_eq!(req.param("status"), Some("active"));
+        assert_eq!(req.param("flag"), Some(""));
+        assert_eq!(req.param("q"), Some("a&b c"));
+        assert_eq!(req.param("missing"), None);
+        assert_eq!(req.param_list("tag"), ["a", "b", "c"]);
+        assert!(req.param_u64("limit").is_err());
+        assert_eq!(req.param_u64("missing"), Ok(None));
+        assert_eq!(req.query, "status=active&tag=a,b&&tag=c&flag&q=a%26b+c&limit=x");
+    }
+
+    #[test]
+    fn non_canonical_record_paths_are_redirected() {
+        let location = |url: &str| {
+            let response = certified_response(&Request::parse(url), "/api/bounties/7", b"{}".to_vec(), CACHE_RECORD);
+            assert_eq!(response.status_code, 308, "{}", url);
+            response.headers.into_iter().find(|(name, _)| name == "Location").map(|(_, value)| value)
+        };
+        assert_eq!(location("/api/bounties/7/").as_deref(), Some("/api/bounties/7"));
+        assert_eq!(location("/api/bounties/%37").as_deref(), Some("/api/bounties/7"));
+        assert_eq!(location("/api//bounties/007?x=1").as_deref(), Some("/api/bounties/7?x=1"));
+    }
+}
@@ -0,0 +1,215 @@
+use candid::Principal;
+use ic_stable_structures::StableBTreeMap;
+use crate::github;
+use crate::state::{Memory, ACTIVE_DEADLINE_INDEX, BOUNTIES, CREATOR_INDEX, DEADLINE_INDEX, ISSUE_INDEX, LEDGER_INDEX, STATUS_INDEX};
+use crate::types::{Bounty, BountyStatus, GithubIssueRef, IssueKey};
+
+// Secondary indices over BOUNTIES. They are only written from `save_bounty`, in the same
+// message as the bounty itself, so they can never disagree with the primary map.
+
+/// Bring every secondary index in line with `bounty`, given the record it replaces.
+pub fn reindex(previous: Option<&Bounty>, bounty: &Bounty) {
+    let id = bounty.id;
+    let old_status = previous.map(|p| p.status.code());
+    if old_status != Some(bounty.status.code()) {
+        STATUS_INDEX.with(|idx| {
+            let mut idx = idx.borrow_mut();
+            if let Some(code) = old_status {
+                idx.remove(&(code, id));
+            }
+            idx.insert((bounty.status.code(), id), ());
+        });
+    }
+    let old_deadline = previous.and_then(|p| p.deadline);
+    if previous.is_none() || old_deadline != bounty.deadline {
+        DEADLINE_INDEX.with(|idx| {
+            let mut idx = idx.borrow_mut();
+            if let Some(deadline) = old_deadline {
+                idx.remove(&(deadline, id));
+            }
+            if let Some(deadline) = bounty.deadline {
+                idx.insert((deadline, id), ());
+            }
+        });
+    }
+    let old_active = previous.and_then(active_deadline_key);
+    let new_active = active_deadline_key(bounty);
+    if old_active != new_active {
+        ACTIVE_DEADLINE_INDEX.with(|idx| {
+            let mut idx = idx.borrow_mut();
+            if let Some(key) = old_active {
+                idx.remove(&key);
+            }
+            // A new or moved deadline has not been warned about yet
+            if let Some(key) = new_active {
+                idx.insert(key, false);
+            }
+        });
+    }
+    // Creator, ledger and issue are fixed at creation
+    if previous.is_none() {
+        CREATOR_INDEX.with(|idx| idx.borrow_mut().insert((bounty.creator, id), ()));
+        LEDGER_INDEX.with(|idx| idx.borrow_mut().insert((bounty.token_ledger, id), ()));
+        index_issue(bounty);
+    }
+}
+
+fn active_deadline_key(bounty: &Bounty) -> Option<(u64, u64)> {
+    bounty.deadline.filter(|_| bounty.status == BountyStatus::Active).map(|deadline| (deadline, bounty.id))
+}
+
+// Bounties from before issue URLs were validated may not parse; they stay unindexed
+fn index_issue(bounty: &Bounty) {
+    if let Ok(issue) = github::parse_issue(&bounty.github_issue_url) {
+        ISSUE_INDEX.with(|idx| idx.borrow_mut().insert(IssueKey { issue, bounty_id: bounty.id }, ()));
+    }
+}
+
+/// Index bounties created before the secondary indices existed. Safe to call repeatedly.
+pub fn backfill() {
+    let indexed = STATUS_INDEX.with(|idx| idx.borrow().len());
+    if indexed == BOUNTIES.with(|b| b.borrow().len()) {
+        return;
+    }
+    BOUNTIES.with(|bounties| {
+        for (_, bounty) in bounties.borrow().iter() {
+            reindex(None, &bounty);
+        }
+    });
+}
+
+/// Index the issues of bounties created before ISSUE_INDEX existed. Safe to call repeatedly.
+pub fn backfill_issues() {
+    if ISSUE_INDEX.with(|idx| !idx.borrow().is_empty()) {
+        return;
+    }
+    BOUNTIES.with(|bounties| {
+        for (_, bounty) in bounties.borrow().iter() {
+            index_issue(&bounty);
+        }
+    });
+}
+
+/// Index the deadlines of bounties that were Active before ACTIVE_DEADLINE_INDEX
+/// existed. Safe to call repeatedly.
+pub fn backfill_active_deadlines() {
+    if ACTIVE_DEADLINE_INDEX.with(|idx| !idx.borrow().is_empty()) {
+        return;
+    }
+    for bounty in load(ids_by_status(&BountyStatus::Active, 0, u64::MAX)) {
+        if let Some(key) = active_deadline_key(&bounty) {
+            ACTIVE_DEADLINE_INDEX.with(|idx| idx.borrow_mut().insert(key, false));
+        }
+    }
+}
+
+pub fn has_status(bounty_id: u64, status: &BountyStatus) -> bool {
+    STATUS_INDEX.with(|idx| idx.borrow().contains_key(&(status.code(), bounty_id)))
+}
+
+/// Ids of bounties in `status`, ascending.
+pub fn ids_by_status(status: &BountyStatus, offset: u64, limit: u64) -> Vec<u64> {
+    let code = status.code();
+    STATUS_INDEX.with(|idx| {
+        idx.borrow()
+            .range((code, 0)..)
+            .take_while(|((c, _), _)| *c == code)
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|((_, id), _)| id)
+            .collect()
+    })
+}
+
+/// Ids of bounties keyed by a principal (creator or ledger), ascending.
+fn ids_by_principal(
+    idx: &StableBTreeMap<(Principal, u64), (), Memory>,
+    principal: Principal,
+    offset: u64,
+    limit: u64,
+) -> Vec<u64> {
+    idx.range((principal, 0)..)
+        .take_while(|((p, _), _)| *p == principal)
+        .skip(offset as usize)
+        .take(limit as usize)
+        .map(|((_, id), _)| id)
+        .collect()
+}
+
+pub fn ids_by_creator(creator: Principal, offset: u64, limit: u64) -> Vec<u64> {
+    CREATOR_INDEX.with(|idx| ids_by_principal(&idx.borrow(), creator, offset, limit))
+}
+
+/// Every ledger that at least one bounty is denominated in.
+pub fn ledgers() -> Vec<Principal> {
+    let mut ledgers: Vec<Principal> = LEDGER_INDEX.with(|idx| idx.borrow().iter().map(|((ledger, _), _)| ledger).collect());
+    ledgers.dedup();
+    ledgers
+}
+
+pub fn ids_by_ledger(token_ledger: Principal, offset: u64, limit: u64) -> Vec<u64> {
+    LEDGER_INDEX.with(|idx| ids_by_principal(&idx.borrow(), token_ledger, offset, limit))
+}
+
+/// Ids of bounties whose deadline falls in `[from, to)`, soonest first.
+pub fn ids_by_deadline(from: u64, to: u64, limit: u64) -> Vec<u64> {
+    DEADLINE_INDEX.with(|idx| {
+        idx.borrow()
+            .range((from, 0)..(to, 0))
+            .take(limit as usize)
+            .map(|((_, id), _)| id)
+            .collect()
+    })
+}
+
+/// Ids of every bounty on `issue`, ascending.
+pub fn ids_by_issue(issue: &GithubIssueRef) -> Vec<u64> {
+    let start = IssueKey { issue: issue.clone(), bounty_id: 0 };
+    ISSUE_INDEX.with(|idx| {
+        idx.borrow()
+            .range(start..)
+            .take_while(|(key, _)| key.issue == *issue)
+            .map(|(key, _)| key.bounty_id)
+            .collect()
+    })
+}
+
+/// Up to `limit` Active bounties whose deadline is before `now`, most overdue first.
+pub fn overdue_active(now: u64, limit: u64) -> Vec<u64> {
+    ACTIVE_DEADLINE_INDEX.with(|idx| {
+        idx.borrow().range(..(now, 0)).take(limit as usize).map(|((_, id), _)| id).collect()
+    })
+}
+
+/// Up to `limit` (deadline, bounty id) of Active bounties due in `[from, to)`
+/// that have not been warned about, soonest first.
+pub fn unwarned_deadlines(from: u64, to: u64, limit: u64) -> Vec<(u64, u64)> {
+    if from >= to {
+        return vec![];
+    }
+    ACTIVE_DEADLINE_INDEX.with(|idx| {
+        idx.borrow()
+            .range((from, 0)..(to, 0))
+            .filter(|(_, warned)| !warned)
+            .take(limit as usize)
+            .map(|(key, _)| key)
+            .collect()
+    })
+}
+
+/// Record that the warning for this (deadline, bounty id) went out.
+pub fn mark_deadline_warned(key: (u64, u64)) {
+    ACTIVE_DEADLINE_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        if idx.contains_key(&key) {
+            idx.insert(key, true);
+        }
+    });
+}
+
+pub fn load(ids: impl IntoIterator<Item = u64>) -> Vec<Bounty> {
+    BOUNTIES.with(|bounties| {
+        let bounties_ref = bounties.borrow();
+        ids.into_iter().filter_map(|id| bounties_ref.get(&id)).collect()
+    })
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeSet;
+use crate::state::LABEL_INDEX;
+use crate::types::{BountyLabels, LabelKey, LabelKind};
+
+const MAX_LABELS_PER_KIND: usize = 10;
+const MAX_LABEL_LENGTH: usize = 32;
+
+// Lowercase and trim a label; allow characters seen in names like "c++", "c#" or ".net"
+fn normalize_label(raw: &str) -> Result<String, String> {
+    let label = raw.to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+    if label.is_empty() || label.chars().count() > MAX_LABEL_LENGTH {
+        return Err(format!("Labels must be 1-{} characters", MAX_LABEL_LENGTH));
+    }
+    if !label.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '+' | '#' | '.')) {
+        return Err(format!("Label '{}' contains unsupported characters", raw));
+    }
+    Ok(label)
+}
+
+fn normalize_list(raw: &[String]) -> Result<Vec<String>, String> {
+    let mut out: Vec<String> = Vec::with_capacity(raw.len());
+    for label in raw {
+        let label = normalize_label(label)?;
+        if !out.contains(&label) {
+            out.push(label);
+        }
+    }
+    if out.len() > MAX_LABELS_PER_KIND {
+        return Err(format!("At most {} labels of each kind are allowed", MAX_LABELS_PER_KIND));
+    }
+    Ok(out)
+}
+
+/// Validate and canonicalize labels as they are stored and indexed.
+pub fn normalize(labels: BountyLabels) -> Result<BountyLabels, String> {
+    Ok(BountyLabels {
+        tags: normalize_list(&labels.tags)?,
+        languages: normalize_list(&labels.languages)?,
+        skills: normalize_list(&labels.skills)?,
+        estimated_hours: labels.estimated_hours,
+    })
+}
+
+fn keys(bounty_id: u64, labels: &BountyLabels) -> Vec<LabelKey> {
+    let kinds = [
+        (LabelKind::Tag, &labels.tags),
+        (LabelKind::Language, &labels.languages),
+        (LabelKind::Skill, &labels.skills),
+    ];
+    kinds.iter()
+        .flat_map(|(kind, values)| values.iter().map(move |value| LabelKey {
+            kind: *kind,
+            value: value.clone(),
+            bounty_id,
+        }))
+        .collect()
+}
+
+pub fn index(bounty_id: u64, labels: &BountyLabels) {
+    LABEL_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        for key in keys(bounty_id, labels) {
+            idx.insert(key, ());
+        }
+    });
+}
+
+pub fn unindex(bounty_id: u64, labels: &BountyLabels) {
+    LABEL_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        for key in keys(bounty_id, labels) {
+            idx.remove(&key);
+        }
+    });
+}
+
+/// Ids of bounties carrying `value` as a label of `kind`, in ascending order.
+pub fn bounty_ids(kind: LabelKind, value: &str) -> BTreeSet<u64> {
+    let start = LabelKey { kind, value: value.to_string(), bounty_id: 0 };
+    LABEL_INDEX.with(|idx| {
+        idx.borrow()
+            .range(start..)
+            .take_while(|(key, _)| key.kind == kind && key.value == value)
+            .map(|(key, _)| key.bounty_id)
+            .collect()
+    })
+}
+
+/// Bounties matching every requested label, or None if no label filter was given.
+pub fn matching_bounty_ids(tags: &[String], languages: &[String], skills: &[String]) -> Result<Option<BTreeSet<u64>>, String> {
+    let mut result: Option<BTreeSet<u64>> = None;
+    let wanted = [
+        (LabelKind::Tag, tags),
+        (LabelKind::Language, languages),
+        (LabelKind::Skill, skills),
+    ];
+    for (kind, values) in wanted {
+        for value in values {
+            let ids = bounty_ids(kind, &normalize_label(value)?);
+            result = Some(match result {
+                Some(acc) => acc.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Distinct labels of one kind with the number of bounties using each.
+pub fn label_counts(kind: LabelKind) -> Vec<(String, u64)> {
+    let start = LabelKey { kind, value: String::new(), bounty_id: 0 };
+    let mut counts: Vec<(String, u64)> = Vec::new();
+    LABEL_INDEX.with(|idx| {
+        for (key, _) in idx.borrow().range(start..).take_while(|(key, _)| key.kind == kind) {
+            match counts.last_mut() {
+                Some((value, count)) if *value == key.value => *count += 1,
+                _ => counts.push((key.value, 1)),
+            }
+        }
+    });
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn labels_are_lowercased_and_hyphenated() {
+        assert_eq!(normalize_label("Rust").unwrap(), "rust");
+        assert_eq!(normalize_label("  Good First Issue \t").unwrap(), "good-first-issue");
+        assert_eq!(normalize_label("help \t wanted").unwrap(), "help-wanted");
+        assert_eq!(normalize_label("C++").unwrap(), "c++");
+        assert_eq!(normalize_label("C#").unwrap(), "c#");
+        assert_eq!(normalize_label(".NET").unwrap(), ".net");
+        assert_eq!(normalize_label("Ünïcode").unwrap(), "ünïcode");
+    }
+
+    #[test]
+    fn invalid_labels_are_rejected() {
+        for raw in ["", "   ", "a/b", "<script>", "tag,other", "emoji-🚀"] {
+            assert!(normalize_label(raw).is_err(), "{:?}", raw);
+        }
+        assert!(normalize_label(&"x".repeat(MAX_LABEL_LENGTH)).is_ok());
+        assert!(normalize_label(&"x".repeat(MAX_LABEL_LENGTH + 1)).is_err());
+        // The limit counts characters, not bytes
+        assert!(normalize_label(&"é".repeat(MAX_LABEL_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn lists_are_deduplicated_in_order() {
+        assert_eq!(normalize_list(&list(&["Rust", "wasm", "rust ", "WASM", "ic"])).unwrap(), ["rust", "wasm", "ic"]);
+    }
+
+    #[test]
+    fn label_count_is_limited_after_deduplication() {
+        let distinct: Vec<String> = (0..MAX_LABELS_PER_KIND).map(|i| format!("tag{}", i)).collect();
+        assert_eq!(normalize_list(&distinct).unwrap().len(), MAX_LABELS_PER_KIND);
+
+        let mut repeated = distinct.clone();
+        repeated.extend(distinct.iter().map(|l| l.to_uppercase()));
+        assert_eq!(normalize_list(&repeated).unwrap().len(), MAX_LABELS_PER_KIND);
+
+        let mut too_many = distinct;
+        too_many.push("one-more".to_string());
+        assert!(normalize_list(&too_many).is_err());
+    }
+
+    #[test]
+    fn every_kind_is_normalized() {
+        let labels = normalize(BountyLabels {
+            tags: list(&["Good First Issue"]),
+            languages: list(&["TypeScript"]),
+            skills: list(&["Front End"]),
+            estimated_hours: Some(4),
+        })
+        .unwrap();
+        assert_eq!(labels.tags, ["good-first-issue"]);
+        assert_eq!(labels.languages, ["typescript"]);
+        assert_eq!(labels.skills, ["front-end"]);
+        assert_eq!(labels.estimated_hours, Some(4));
+        assert!(normalize(BountyLabels { skills: list(&["bad/skill"]), ..labels }).is_err());
+    }
+}
@@ -1,5 +1,4 @@
 use candid::{CandidType, Deserialize, Nat, Principal};
-use ic_cdk_macros::*;
 
 use ic_cdk::{update, query};
 use crate::types::*;
@@ -9,11 +8,30 @@ use crate::state::*;
 mod types;
 mod state;
 mod escrow;
+mod auth;
+mod series;
+mod labels;
+mod search;
+mod listing;
+mod indices;
+mod submissions;
+mod lifecycle;
+mod events;
+mod certification;
+mod http;
+mod badge;
+mod feed;
+mod github;
+mod webhook;
+mod repos;
+mod notifications;
+
+use crate::auth::{authorize, authorize_maintainers, authorize_org, authorize_series, org_role};
 
 const CANISTER_VERSION: &str = "1.0.0";
 use std::cell::Cell;
 thread_local! {
-    static LAST_UPDATED: Cell<u64> = Cell::new(0);
+    static LAST_UPDATED: Cell<u64> = const { Cell::new(0) };
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -31,6 +49,24 @@ fn update_last_updated() {
 #[ic_cdk::init]
 fn init() {
     ic_cdk::println!("Avox Backend Canister Initialized");
+    SCHEMA_VERSION.with(|v| v.borrow_mut().set(CURRENT_SCHEMA_VERSION).expect("Failed to set schema version"));
+    series::start_timer();
+    notifications::start_timer();
+}
+
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    submissions::migrate_embedded();
+    backfill_participation();
+    certification::rebuild();
+    indices::backfill();
+    indices::backfill_issues();
+    indices::backfill_active_deadlines();
+    search::backfill();
+    listing::backfill();
+    listing::start_decimals_refresh();
+    series::start_timer();
+    notifications::start_timer();
 }
 
 #[ic_cdk::query]
@@ -43,22 +79,224 @@ fn greet(name: String) -> String {
 pub fn update_user_profile(request: UpdateUserProfileRequest) -> Result<(), String> {
     update_last_updated();
     let caller = ic_cdk::caller();
-    USER_PROFILES.with(|profiles| {
-        let mut profiles = profiles.borrow_mut();
-        let mut profile = profiles.get(&caller).map(|x| x.clone()).unwrap_or_else(|| UserProfile::default());
-        if let Some(name) = request.name { profile.name = Some(name); }
-        if let Some(github) = request.github { profile.github = Some(github); }
-        if let Some(twitter) = request.twitter { profile.twitter = Some(twitter); }
-        if let Some(pfp_url) = request.pfp_url { profile.pfp_url = Some(pfp_url); }
-        profiles.insert(caller, profile);
-    });
+    let mut profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&caller)).unwrap_or_default();
+    if let Some(name) = request.name { profile.name = Some(name); }
+    if let Some(github) = request.github {
+        // Renaming away from a verified account drops the verification
+        if profile.github_verified.as_ref().is_some_and(|v| !v.login.eq_ignore_ascii_case(&github)) {
+            unlink_github(&mut profile);
+        }
+        profile.github = Some(github);
+    }
+    if let Some(twitter) = request.twitter { profile.twitter = Some(twitter); }
+    if let Some(pfp_url) = request.pfp_url { profile.pfp_url = Some(pfp_url); }
+    save_profile(caller, profile);
+    events::record(caller, None, EventKind::ProfileUpdated);
+    Ok(())
+}
+
+const GITHUB_CHALLENGE_TTL_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+const MAX_GITHUB_PROOF_ATTEMPTS: u32 = 5;
+
+/// Start proving ownership of a GitHub account. Publish the returned token in a
+/// public gist owned by the account, then call `complete_github_link` with the
+/// gist's URL.
+#[update]
+pub async fn start_github_link(login: String) -> Result<GithubChallenge, String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    if caller == Principal::anonymous() {
+        return Err("Anonymous principals cannot link a GitHub account".to_string());
+    }
+    let valid_login = !login.is_empty()
+        && login.len() <= 39
+        && login.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !login.starts_with('-');
+    if !valid_login {
+        return Err("Invalid GitHub login".to_string());
+    }
+    // Failed attempts carry over to a new challenge until the previous one has
+    // expired, so restarting does not buy more proof checks (each an outcall)
+    let attempts = carried_github_attempts(caller, ic_cdk::api::time())?;
+    let (random,) = ic_cdk::api::management_canister::main::raw_rand()
+        .await
+        .map_err(|(code, msg)| format!("Failed to get randomness: {:?} {}", code, msg))?;
+    let now = ic_cdk::api::time();
+    let attempts = attempts.max(carried_github_attempts(caller, now)?);
+    let challenge = GithubChallenge {
+        login: login.to_lowercase(),
+        token: format!("avox-github-proof:{}:{}", caller.to_text(), hex::encode(&random[..16])),
+        issued_at: now,
+        expires_at: now.saturating_add(GITHUB_CHALLENGE_TTL_NS),
+        attempts,
+    };
+    GITHUB_CHALLENGES.with(|c| c.borrow_mut().insert(caller, challenge.clone()));
+    Ok(challenge)
+}
+
+// Attempts used on the caller's unexpired challenge, or an error once they are exhausted
+fn carried_github_attempts(caller: Principal, now: u64) -> Result<u32, String> {
+    match GITHUB_CHALLENGES.with(|c| c.borrow().get(&caller)).filter(|c| now <= c.expires_at) {
+        Some(previous) if previous.attempts >= MAX_GITHUB_PROOF_ATTEMPTS => Err(format!(
+            "Too many attempts; a new GitHub challenge can be started after {}",
+            previous.expires_at
+        )),
+        Some(previous) => Ok(previous.attempts),
+        None => Ok(0),
+    }
+}
+
+/// Finish a GitHub link started with `start_github_link`. The proof must be owned
+/// by the challenged account and contain the challenge token.
+#[update]
+pub async fn complete_github_link(proof_url: String) -> Result<VerifiedGithub, String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let mut challenge = GITHUB_CHALLENGES.with(|c| c.borrow().get(&caller))
+        .ok_or("No pending GitHub challenge; call start_github_link first")?;
+    let now = ic_cdk::api::time();
+    if now > challenge.expires_at {
+        GITHUB_CHALLENGES.with(|c| c.borrow_mut().remove(&caller));
+        return Err("GitHub challenge has expired; start a new one".to_string());
+    }
+    if challenge.attempts >= MAX_GITHUB_PROOF_ATTEMPTS {
+        return Err("Too many attempts; a new GitHub challenge can be started once this one expires".to_string());
+    }
+    // Count the attempt before the outcall so retries are bounded
+    challenge.attempts += 1;
+    GITHUB_CHALLENGES.with(|c| c.borrow_mut().insert(caller, challenge.clone()));
+
+    let (owner, content) = github::fetch_proof(&proof_url).await?;
+    if owner != challenge.login {
+        return Err(format!("Proof belongs to {}, not {}", owner, challenge.login));
+    }
+    if !content.contains(&challenge.token) {
+        return Err("Proof does not contain the challenge token".to_string());
+    }
+    // A login is verified for one principal at a time; a fresh proof moves it
+    if let Some(previous) = GITHUB_LOGINS.with(|l| l.borrow().get(&challenge.login)).filter(|p| *p != caller) {
+        if let Some(mut other) = USER_PROFILES.with(|profiles| profiles.borrow().get(&previous)) {
+            unlink_github(&mut other);
+            save_profile(previous, other);
+        }
+    }
+    let mut profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&caller)).unwrap_or_default();
+    unlink_github(&mut profile);
+    let verified = VerifiedGithub { login: challenge.login.clone(), proof_url, verified_at: now };
+    GITHUB_LOGINS.with(|l| l.borrow_mut().insert(challenge.login.clone(), caller));
+    profile.github = Some(challenge.login);
+    profile.github_verified = Some(verified.clone());
+    save_profile(caller, profile);
+    GITHUB_CHALLENGES.with(|c| c.borrow_mut().remove(&caller));
+    events::record(caller, None, EventKind::ProfileUpdated);
+    Ok(verified)
+}
+
+fn unlink_github(profile: &mut UserProfile) {
+    if let Some(verified) = profile.github_verified.take() {
+        GITHUB_LOGINS.with(|l| l.borrow_mut().remove(&verified.login));
+    }
+}
+
+// Repository owners manage their own webhook secret once their GitHub login is
+// verified; controllers can manage any
+fn authorize_repo(caller: Principal, repo: &str) -> Result<(), String> {
+    if ic_cdk::api::is_controller(&caller) {
+        return Ok(());
+    }
+    let owner = repo.split('/').next().unwrap_or_default();
+    let verified = USER_PROFILES.with(|profiles| profiles.borrow().get(&caller)).and_then(|p| p.github_verified);
+    match verified {
+        Some(v) if v.login == owner => Ok(()),
+        Some(_) => Err(format!("Only {} or a controller can manage this repository's webhook", owner)),
+        None => Err("Link a verified GitHub account first".to_string()),
+    }
+}
+
+/// Register the secret GitHub signs webhook deliveries for `repo` ("owner/repo")
+/// with. Point the webhook at `https://<canister>.icp0.io/webhooks/github`.
+#[update]
+pub fn set_repo_webhook_secret(repo: String, secret: String) -> Result<(), String> {
+    update_last_updated();
+    let repo = webhook::repo_key(&repo)?;
+    authorize_repo(ic_cdk::caller(), &repo)?;
+    webhook::set_secret(repo, secret)
+}
+
+#[update]
+pub fn remove_repo_webhook_secret(repo: String) -> Result<(), String> {
+    update_last_updated();
+    let repo = webhook::repo_key(&repo)?;
+    authorize_repo(ic_cdk::caller(), &repo)?;
+    if !webhook::remove_secret(&repo) {
+        return Err("No webhook secret registered for this repository".to_string());
+    }
     Ok(())
 }
 
+/// Whether `repo` has a webhook secret; the secret itself is never returned.
+#[query]
+pub fn has_repo_webhook_secret(repo: String) -> Result<bool, String> {
+    Ok(webhook::has_secret(&webhook::repo_key(&repo)?))
+}
+
+#[query]
+pub fn get_github_challenge() -> Option<GithubChallenge> {
+    GITHUB_CHALLENGES.with(|c| c.borrow().get(&ic_cdk::caller()))
+}
+
+/// The principal that has proven control of a GitHub login, if any.
+#[query]
+pub fn get_principal_by_github(login: String) -> Option<Principal> {
+    GITHUB_LOGINS.with(|l| l.borrow().get(&login.to_lowercase()))
+}
+
 /// Get a user's profile and stats.
 #[query]
 pub fn get_user_profile(user: Principal) -> Option<UserProfile> {
-    USER_PROFILES.with(|profiles| profiles.borrow().get(&user).map(|x| x.clone()))
+    USER_PROFILES.with(|profiles| profiles.borrow().get(&user))
+}
+
+// Write a bounty back and keep every derived index in step with it
+fn save_bounty(bounty: Bounty) {
+    let previous = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty.id));
+    indices::reindex(previous.as_ref(), &bounty);
+    search::index_bounty(&bounty);
+    listing::reindex(previous.as_ref(), &bounty);
+    certification::certify_bounty(&bounty);
+    BOUNTIES.with(|bounties| {
+        bounties.borrow_mut().insert(bounty.id, bounty);
+    });
+}
+
+// Write a profile back and certify it
+fn save_profile(principal: Principal, profile: UserProfile) {
+    certification::certify_profile(principal, &profile);
+    USER_PROFILES.with(|profiles| {
+        profiles.borrow_mut().insert(principal, profile);
+    });
+}
+
+// bounties_participated was never incremented before; recount it from the
+// bounties each user submitted to. Runs before certification::rebuild, which
+// certifies the corrected profiles.
+fn backfill_participation() {
+    let counts: Vec<(Principal, u64)> = USER_SUBMISSIONS.with(|user_subs| {
+        user_subs.borrow()
+            .iter()
+            .map(|(user, ids)| (user, ids.0.iter().collect::<std::collections::BTreeSet<_>>().len() as u64))
+            .collect()
+    });
+    USER_PROFILES.with(|profiles| {
+        let mut profiles = profiles.borrow_mut();
+        for (user, count) in counts {
+            let mut profile = profiles.get(&user).unwrap_or_default();
+            if profile.bounties_participated < count {
+                profile.bounties_participated = count;
+                profiles.insert(user, profile);
+            }
+        }
+    });
 }
 
 fn is_bounty_expired(bounty: &Bounty) -> bool {
@@ -68,19 +306,49 @@ fn is_bounty_expired(bounty: &Bounty) -> bool {
     }
 }
 
-#[update]
-pub async fn create_bounty(request: CreateBountyRequest) -> Result<(u64, String), String> {
-    update_last_updated();
-    let caller = ic_cdk::caller();
+fn validate_bounty_request(caller: Principal, request: &CreateBountyRequest) -> Result<(), String> {
     if request.title.is_empty() {
         return Err("Title cannot be empty".to_string());
     }
-    if request.github_issue_url.is_empty() {
-        return Err("GitHub issue URL cannot be empty".to_string());
+    let issue = github::parse_issue(&request.github_issue_url)?;
+    if request.stack != Some(true) {
+        check_issue_free(&issue)?;
     }
     if request.prize_amount == 0u64 {
         return Err("Prize amount must be greater than 0".to_string());
     }
+    if request.max_reservation_ns == Some(0) {
+        return Err("Maximum reservation window must be greater than 0".to_string());
+    }
+    if request.competition_mode == Some(CompetitionMode::Exclusive) && request.max_reservation_ns.is_none() {
+        return Err("Exclusive bounties must set a maximum reservation window".to_string());
+    }
+    if let Some(org_id) = request.org_id {
+        if !ORGANIZATIONS.with(|orgs| orgs.borrow().contains_key(&org_id)) {
+            return Err("Organization not found".to_string());
+        }
+        authorize_org(org_id, caller, &[OrgRole::Owner, OrgRole::Admin])?;
+    }
+    if let Some(bounty_labels) = &request.labels {
+        labels::normalize(bounty_labels.clone())?;
+    }
+    Ok(())
+}
+
+// One open bounty per issue unless the creator explicitly stacks another on it
+fn check_issue_free(issue: &GithubIssueRef) -> Result<(), String> {
+    let open = indices::ids_by_issue(issue)
+        .into_iter()
+        .find(|id| indices::has_status(*id, &BountyStatus::PendingFunding) || indices::has_status(*id, &BountyStatus::Active));
+    match open {
+        Some(id) => Err(format!("Bounty {} is already open on this issue; set stack to add another bounty to it", id)),
+        None => Ok(()),
+    }
+}
+
+// Persist a validated request as a new PendingFunding bounty.
+// Returns the bounty id and its escrow subaccount.
+fn store_new_bounty(creator: Principal, request: CreateBountyRequest, series_id: Option<u64>) -> (u64, [u8; 32]) {
     let now = ic_cdk::api::time();
     let bounty_id = BOUNTY_COUNTER.with(|counter| {
         let mut counter_ref = counter.borrow_mut();
@@ -90,25 +358,65 @@ pub async fn create_bounty(request: CreateBountyRequest) -> Result<(u64, String)
         new_id
     });
     let subaccount = escrow::generate_subaccount(bounty_id);
-    let escrow_account = escrow::get_escrow_account(subaccount);
+    // Already validated by validate_bounty_request
+    let github_issue_url = github::parse_issue(&request.github_issue_url).map_or(request.github_issue_url, |issue| issue.url());
+    let bounty_labels = request.labels.and_then(|l| labels::normalize(l).ok());
     let bounty = Bounty {
         id: bounty_id,
-        creator: caller,
-        title: request.title.clone(),
-        description: request.description.clone(),
-        github_issue_url: request.github_issue_url.clone(),
-        prize_amount: request.prize_amount.clone(),
+        creator,
+        title: request.title,
+        description: request.description,
+        github_issue_url,
+        prize_amount: request.prize_amount,
         token_ledger: request.token_ledger,
         status: BountyStatus::PendingFunding,
-        submissions: vec![],
         winner: None,
         created_at: now,
         escrow_subaccount: subaccount,
         deadline: request.deadline,
+        competition_mode: request.competition_mode,
+        max_reservation_ns: request.max_reservation_ns,
+        org_id: request.org_id,
+        series_id,
+        labels: bounty_labels.clone(),
+        pr_requirement: request.pr_requirement,
+        issue_closed_at: None,
+        funded_at: None,
     };
-    BOUNTIES.with(|bounties| {
-        bounties.borrow_mut().insert(bounty_id, bounty);
-    });
+    lifecycle::record_creation(&bounty, creator);
+    let created = EventKind::BountyCreated {
+        creator,
+        title: bounty.title.clone(),
+        prize_amount: bounty.prize_amount.clone(),
+        token_ledger: bounty.token_ledger,
+    };
+    save_bounty(bounty);
+    events::record(creator, Some(bounty_id), created);
+    if let Some(bounty_labels) = &bounty_labels {
+        labels::index(bounty_id, bounty_labels);
+    }
+    if let Some(org_id) = request.org_id {
+        ORG_BOUNTIES.with(|org_bounties| {
+            let mut ob = org_bounties.borrow_mut();
+            let mut bounty_list = ob.get(&org_id).unwrap_or(BountyIdList(vec![]));
+            bounty_list.0.push(bounty_id);
+            ob.insert(org_id, bounty_list);
+        });
+    }
+    (bounty_id, subaccount)
+}
+
+#[update]
+pub async fn create_bounty(request: CreateBountyRequest) -> Result<(u64, String), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    validate_bounty_request(caller, &request)?;
+    // Also checks that token_ledger really is an ICRC-1 ledger
+    escrow::token_decimals(request.token_ledger).await?;
+    // Another bounty may have claimed the issue while the ledger was queried
+    validate_bounty_request(caller, &request)?;
+    let (bounty_id, subaccount) = store_new_bounty(caller, request, None);
+    let escrow_account = escrow::get_escrow_account(subaccount);
     Ok((bounty_id, escrow_account.to_string()))
 }
 
@@ -124,6 +432,40 @@ pub fn get_escrow_account(bounty_id: u64) -> Result<String, String> {
     })
 }
 
+/// The escrow account for a bounty with a certificate and witness for
+/// `escrow/<id>`, so a client can verify the address before sending funds.
+#[query]
+pub fn get_certified_escrow_account(bounty_id: u64) -> Result<CertifiedEscrowAccount, String> {
+    let bounty = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)).ok_or("Bounty not found")?;
+    Ok(CertifiedEscrowAccount {
+        account: certification::escrow_payload(&bounty),
+        certificate: certification::certificate()?,
+        witness: certification::escrow_witness(bounty_id)?,
+    })
+}
+
+/// The candid-encoded bounty with a certificate and witness for `bounty/<id>`.
+#[query]
+pub fn get_certified_bounty(bounty_id: u64) -> Result<CertifiedRecord, String> {
+    let bounty = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)).ok_or("Bounty not found")?;
+    Ok(CertifiedRecord {
+        data: certification::bounty_payload(&bounty),
+        certificate: certification::certificate()?,
+        witness: certification::bounty_witness(bounty_id)?,
+    })
+}
+
+/// The candid-encoded profile with a certificate and witness for `profile/<principal>`.
+#[query]
+pub fn get_certified_user_profile(user: Principal) -> Result<CertifiedRecord, String> {
+    let profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&user)).ok_or("Profile not found")?;
+    Ok(CertifiedRecord {
+        data: certification::profile_payload(&profile),
+        certificate: certification::certificate()?,
+        witness: certification::profile_witness(user)?,
+    })
+}
+
 #[update]
 pub async fn verify_escrow_deposit(bounty_id: u64) -> Result<bool, String> {
     update_last_updated();
@@ -148,30 +490,20 @@ pub async fn verify_escrow_deposit(bounty_id: u64) -> Result<bool, String> {
     };
     if let Some(deadline) = deadline {
         if ic_cdk::api::time() > deadline && status == Some(BountyStatus::Active) {
-            // Mark as expired
-            BOUNTIES.with(|bounties| {
-                let mut bounties_ref = bounties.borrow_mut();
-                if let Some(mut bounty) = bounties_ref.get(&bounty_id) {
-                    bounty.status = BountyStatus::Expired;
-                    bounties_ref.insert(bounty_id, bounty);
-                }
-            });
+            if let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) {
+                lifecycle::transition(bounty, BountyStatus::Expired, ic_cdk::caller())?;
+            }
             return Ok(false);
         }
     }
     let balance = escrow::check_balance(token_ledger, subaccount).await?;
     if balance >= prize_amount {
-        // Mark bounty as Active if not already
-        BOUNTIES.with(|bounties| {
-            let mut bounties_ref = bounties.borrow_mut();
-            if let Some(bounty) = bounties_ref.get(&bounty_id) {
-                if bounty.status != BountyStatus::Active {
-                    let mut updated_bounty = bounty.clone();
-                    updated_bounty.status = BountyStatus::Active;
-                    bounties_ref.insert(bounty_id, updated_bounty);
-                }
+        // Only an unfunded bounty is activated; later statuses are left alone
+        if let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) {
+            if bounty.status == BountyStatus::PendingFunding {
+                lifecycle::transition(bounty, BountyStatus::Active, ic_cdk::caller())?;
             }
-        });
+        }
         funded = true;
     }
     Ok(funded)
@@ -180,12 +512,7 @@ pub async fn verify_escrow_deposit(bounty_id: u64) -> Result<bool, String> {
 macro_rules! check_expired_and_update {
     ($bounty:expr, $bounty_id:expr) => {
         if is_bounty_expired(&$bounty) && $bounty.status == BountyStatus::Active {
-            BOUNTIES.with(|bounties| {
-                let mut bounties_ref = bounties.borrow_mut();
-                let mut updated = $bounty.clone();
-                updated.status = BountyStatus::Expired;
-                bounties_ref.insert($bounty_id, updated);
-            });
+            lifecycle::transition($bounty.clone(), BountyStatus::Expired, ic_cdk::caller())?;
             return Err("Bounty has expired".to_string());
         }
     };
@@ -198,210 +525,879 @@ pub fn submit_solution(request: SubmitSolutionRequest) -> Result<(), String> {
     if request.pr_url.is_empty() {
         return Err("PR URL cannot be empty".to_string());
     }
-    BOUNTIES.with(|bounties| {
-        let mut bounties_ref = bounties.borrow_mut();
-        if let Some(mut bounty) = bounties_ref.get(&request.bounty_id) {
-            check_expired_and_update!(bounty, request.bounty_id);
-            if bounty.status != BountyStatus::Active {
-                return Err("Bounty is not active".to_string());
-            }
-            if bounty.submissions.iter().any(|s| s.submitter == caller) {
-                return Err("You have already submitted a solution".to_string());
-            }
-            let submission = Submission {
-                submitter: caller,
-                pr_url: request.pr_url,
-                comment: request.comment,
-                submitted_at: ic_cdk::api::time(),
-            };
-            bounty.submissions.push(submission);
-            bounties_ref.insert(request.bounty_id, bounty);
-            // Track user submission
-            USER_SUBMISSIONS.with(|user_subs| {
-                let mut us = user_subs.borrow_mut();
-                let mut sub_list = us.get(&caller).map(|x| x.clone()).unwrap_or(BountyIdList(vec![]));
-                sub_list.0.push(request.bounty_id);
-                us.insert(caller, sub_list);
-            });
-            // Increment bounties_participated in user profile (only once per bounty)
-            let is_first_submission = USER_SUBMISSIONS.with(|user_subs| {
-                let user_subs = user_subs.borrow();
-                !user_subs.get(&caller).map(|x| !x.0.is_empty()).unwrap_or(true)
-            });
-            if is_first_submission {
-                USER_PROFILES.with(|profiles| {
-                    let mut profiles = profiles.borrow_mut();
-                    let mut profile = profiles.get(&caller).map(|x| x.clone()).unwrap_or_else(|| UserProfile::default());
-                    profile.bounties_participated += 1;
-                    profiles.insert(caller, profile);
-                });
-            }
-            Ok(())
-        } else {
-            Err("Bounty not found".to_string())
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&request.bounty_id)
+    }).ok_or("Bounty not found")?;
+    check_expired_and_update!(bounty, request.bounty_id);
+    if bounty.status != BountyStatus::Active {
+        return Err("Bounty is not active".to_string());
+    }
+    // cancel_bounty only refunds a bounty without submissions; none may arrive
+    // while that refund is in flight
+    if lifecycle::PayoutGuard::is_held(request.bounty_id) {
+        return Err("A payout for this bounty is in progress".to_string());
+    }
+    if submissions::has_submitted(request.bounty_id, caller) {
+        return Err("You have already submitted a solution".to_string());
+    }
+    if bounty.competition_mode() == CompetitionMode::Application && !is_approved_applicant(request.bounty_id, caller) {
+        return Err("Only approved applicants can submit to this bounty".to_string());
+    }
+    if let Some(reservation) = get_live_reservation(request.bounty_id) {
+        if reservation.holder != caller && bounty.competition_mode() == CompetitionMode::Exclusive {
+            return Err(format!("Bounty is exclusively reserved by another contributor until {}", reservation.expires_at));
         }
-    })
+        if reservation.holder == caller {
+            // The holder has delivered; free the bounty for others
+            RESERVATIONS.with(|r| r.borrow_mut().remove(&request.bounty_id));
+        }
+    }
+    // The bounty record itself is untouched; only the submission map and its indices change
+    let submission = submissions::add(request.bounty_id, caller, request.pr_url, request.comment, ic_cdk::api::time());
+    listing::update_submission_count(request.bounty_id, &bounty.status, submission.id, submission.id + 1);
+    events::record(caller, Some(request.bounty_id), EventKind::SubmissionAdded { submission_id: submission.id, submitter: caller });
+    // Track user submission
+    USER_SUBMISSIONS.with(|user_subs| {
+        let mut us = user_subs.borrow_mut();
+        let mut sub_list = us.get(&caller).unwrap_or(BountyIdList(vec![]));
+        sub_list.0.push(request.bounty_id);
+        us.insert(caller, sub_list);
+    });
+    // has_submitted above allows one submission per bounty, so each one is a new bounty
+    let mut profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&caller)).unwrap_or_default();
+    profile.bounties_participated += 1;
+    save_profile(caller, profile);
+    Ok(())
+}
+
+// A holder may extend their hold this many times, counting a fresh reservation
+// right after their own lapsed or released one
+const MAX_RESERVATION_RENEWALS: u32 = 3;
+
+fn get_live_reservation(bounty_id: u64) -> Option<Reservation> {
+    let now = ic_cdk::api::time();
+    RESERVATIONS.with(|r| r.borrow().get(&bounty_id)).filter(|r| r.is_live(now))
+}
+
+// Reservations never outlive the bounty deadline
+fn reservation_expiry(bounty: &Bounty, now: u64, duration_ns: u64) -> u64 {
+    let expires_at = now.saturating_add(duration_ns);
+    match bounty.deadline {
+        Some(deadline) => expires_at.min(deadline),
+        None => expires_at,
+    }
+}
+
+fn validate_reservation_duration(bounty: &Bounty, duration_ns: u64) -> Result<(), String> {
+    let max = bounty.max_reservation_ns.ok_or("Reservations are not enabled for this bounty")?;
+    if duration_ns == 0 || duration_ns > max {
+        return Err(format!("Reservation duration must be between 1 and {} nanoseconds", max));
+    }
+    Ok(())
 }
 
+/// Reserve an active bounty for `duration_ns`, signalling that the caller is working on it.
 #[update]
-pub async fn select_winner(bounty_id: u64, winner: Principal) -> Result<(), String> {
+pub fn reserve_bounty(bounty_id: u64, duration_ns: u64) -> Result<Reservation, String> {
     update_last_updated();
     let caller = ic_cdk::caller();
     let bounty = BOUNTIES.with(|bounties| {
         bounties.borrow().get(&bounty_id)
     }).ok_or("Bounty not found")?;
     check_expired_and_update!(bounty, bounty_id);
-    if bounty.creator != caller {
-        return Err("Only bounty creator can select winner".to_string());
-    }
     if bounty.status != BountyStatus::Active {
         return Err("Bounty is not active".to_string());
     }
-    if !bounty.submissions.iter().any(|s| s.submitter == winner) {
-        return Err("Selected winner has not submitted a solution".to_string());
+    if bounty.creator == caller {
+        return Err("Bounty creator cannot reserve their own bounty".to_string());
     }
-    // Update bounty
-    BOUNTIES.with(|bounties| {
-        let mut bounties_ref = bounties.borrow_mut();
-        if let Some(mut bounty) = bounties_ref.get(&bounty_id) {
-            bounty.winner = Some(winner);
-            bounty.status = BountyStatus::Completed;
-            bounties_ref.insert(bounty_id, bounty);
+    validate_reservation_duration(&bounty, duration_ns)?;
+    if submissions::has_submitted(bounty_id, caller) {
+        return Err("You have already submitted a solution".to_string());
+    }
+    if let Some(existing) = get_live_reservation(bounty_id) {
+        if existing.holder == caller {
+            return Err("You already hold a reservation; renew it instead".to_string());
         }
-    });
-    // Increment bounties_won in winner's profile
-    USER_PROFILES.with(|profiles| {
-        let mut profiles = profiles.borrow_mut();
-        let mut profile = profiles.get(&winner).map(|x| x.clone()).unwrap_or_else(|| UserProfile::default());
-        profile.bounties_won += 1;
-        profiles.insert(winner, profile);
-    });
-    Ok(())
+        return Err(format!("Bounty is already reserved until {}", existing.expires_at));
+    }
+    // Reserving again straight after one's own hold ended counts as a renewal
+    let renewals = RESERVATIONS.with(|r| r.borrow().get(&bounty_id))
+        .filter(|previous| previous.holder == caller)
+        .map_or(0, |previous| previous.renewals + 1);
+    if renewals > MAX_RESERVATION_RENEWALS {
+        return Err("You have used all your reservation renewals on this bounty".to_string());
+    }
+    let now = ic_cdk::api::time();
+    let reservation = Reservation {
+        bounty_id,
+        holder: caller,
+        reserved_at: now,
+        expires_at: reservation_expiry(&bounty, now, duration_ns),
+        renewals,
+    };
+    RESERVATIONS.with(|r| r.borrow_mut().insert(bounty_id, reservation.clone()));
+    Ok(reservation)
 }
 
+/// Extend the caller's live reservation by `duration_ns` from now, at most
+/// MAX_RESERVATION_RENEWALS times.
 #[update]
-pub async fn claim_reward(bounty_id: u64) -> Result<Nat, String> {
+pub fn renew_reservation(bounty_id: u64, duration_ns: u64) -> Result<Reservation, String> {
     update_last_updated();
     let caller = ic_cdk::caller();
-    let claim_key = ClaimKey {
-        bounty_id,
-        principal: caller,
-    };
-    if CLAIMS.with(|claims| claims.borrow().get(&claim_key)).is_some() {
-        return Err("Reward already claimed".to_string());
-    }
     let bounty = BOUNTIES.with(|bounties| {
         bounties.borrow().get(&bounty_id)
     }).ok_or("Bounty not found")?;
     check_expired_and_update!(bounty, bounty_id);
-    match bounty.winner {
-        Some(winner) if winner == caller => {},
-        _ => return Err("You are not the winner of this bounty".to_string()),
+    if bounty.status != BountyStatus::Active {
+        return Err("Bounty is not active".to_string());
     }
-    if bounty.status != BountyStatus::Completed {
-        return Err("Bounty is not completed".to_string());
+    validate_reservation_duration(&bounty, duration_ns)?;
+    let mut reservation = match get_live_reservation(bounty_id) {
+        Some(r) if r.holder == caller => r,
+        _ => return Err("You do not hold a live reservation on this bounty".to_string()),
+    };
+    if reservation.renewals >= MAX_RESERVATION_RENEWALS {
+        return Err("You have used all your reservation renewals on this bounty".to_string());
     }
-    let transfer_result = escrow::transfer_from_escrow(
-        bounty.token_ledger,
-        bounty.escrow_subaccount,
-        caller,
-        bounty.prize_amount.clone()
-    ).await?;
-    CLAIMS.with(|claims| {
-        claims.borrow_mut().insert(claim_key, true);
-    });
-    Ok(transfer_result)
+    reservation.expires_at = reservation_expiry(&bounty, ic_cdk::api::time(), duration_ns);
+    reservation.renewals += 1;
+    RESERVATIONS.with(|r| r.borrow_mut().insert(bounty_id, reservation.clone()));
+    Ok(reservation)
 }
 
+/// Drop a reservation early. Allowed for the holder and anyone who can review submissions.
 #[update]
-pub async fn refund_expired_bounty(bounty_id: u64) -> Result<Nat, String> {
+pub fn release_reservation(bounty_id: u64) -> Result<(), String> {
     update_last_updated();
     let caller = ic_cdk::caller();
     let bounty = BOUNTIES.with(|bounties| {
         bounties.borrow().get(&bounty_id)
     }).ok_or("Bounty not found")?;
-    if bounty.creator != caller {
-        return Err("Only the creator can refund the bounty".to_string());
-    }
-    if bounty.status != BountyStatus::Expired {
-        return Err("Bounty is not expired; cannot refund unless status is Expired".to_string());
-    }
-    if bounty.winner.is_some() {
-        return Err("Winner already selected, cannot refund".to_string());
+    let mut reservation = get_live_reservation(bounty_id).ok_or("Bounty has no live reservation")?;
+    if reservation.holder != caller {
+        authorize(&bounty, caller, Permission::ReviewSubmissions)?;
     }
-    let refund_result = escrow::transfer_from_escrow(
-        bounty.token_ledger,
-        bounty.escrow_subaccount,
-        caller,
-        bounty.prize_amount.clone()
-    ).await?;
-    Ok(refund_result)
+    // Kept as lapsed so the holder's renewal count survives a release
+    reservation.expires_at = ic_cdk::api::time();
+    RESERVATIONS.with(|r| r.borrow_mut().insert(bounty_id, reservation));
+    Ok(())
 }
 
+/// Get the live reservation on a bounty, if any. Expired reservations are not returned.
+#[query]
+pub fn get_reservation(bounty_id: u64) -> Option<Reservation> {
+    get_live_reservation(bounty_id)
+}
+
+const MAX_PITCH_LENGTH: usize = 1000;
+
+fn is_approved_applicant(bounty_id: u64, applicant: Principal) -> bool {
+    APPLICATIONS.with(|apps| {
+        apps.borrow()
+            .get(&(bounty_id, applicant))
+            .map(|a| a.status == ApplicationStatus::Approved)
+            .unwrap_or(false)
+    })
+}
+
+/// Apply to work on an application-gated bounty with a short pitch.
 #[update]
-pub async fn cancel_bounty(bounty_id: u64) -> Result<(), String> {
+pub fn apply_for_bounty(request: ApplyForBountyRequest) -> Result<(), String> {
     update_last_updated();
     let caller = ic_cdk::caller();
+    if request.pitch.trim().is_empty() {
+        return Err("Pitch cannot be empty".to_string());
+    }
+    if request.pitch.chars().count() > MAX_PITCH_LENGTH {
+        return Err(format!("Pitch cannot exceed {} characters", MAX_PITCH_LENGTH));
+    }
     let bounty = BOUNTIES.with(|bounties| {
-        bounties.borrow().get(&bounty_id)
+        bounties.borrow().get(&request.bounty_id)
     }).ok_or("Bounty not found")?;
-    check_expired_and_update!(bounty, bounty_id);
-    if bounty.creator != caller {
-        return Err("Only bounty creator can cancel".to_string());
+    check_expired_and_update!(bounty, request.bounty_id);
+    if bounty.competition_mode() != CompetitionMode::Application {
+        return Err("Bounty does not accept applications".to_string());
     }
-    if bounty.status != BountyStatus::Active {
-        return Err("Can only cancel active bounties".to_string());
+    if bounty.status != BountyStatus::Active && bounty.status != BountyStatus::PendingFunding {
+        return Err("Bounty is no longer accepting applications".to_string());
     }
-    if !bounty.submissions.is_empty() {
-        return Err("Cannot cancel bounty with submissions".to_string());
+    if bounty.creator == caller {
+        return Err("Bounty creator cannot apply to their own bounty".to_string());
     }
-    let _refund_result = escrow::transfer_from_escrow(
-        bounty.token_ledger,
-        bounty.escrow_subaccount,
-        caller,
-        bounty.prize_amount.clone()
-    ).await?;
-    BOUNTIES.with(|bounties| {
-        let mut bounties_ref = bounties.borrow_mut();
-        if let Some(mut bounty) = bounties_ref.get(&bounty_id) {
-            bounty.status = BountyStatus::Cancelled;
-            bounties_ref.insert(bounty_id, bounty);
+    let key = (request.bounty_id, caller);
+    let previous = APPLICATIONS.with(|apps| apps.borrow().get(&key));
+    if let Some(previous) = &previous {
+        if previous.status != ApplicationStatus::Withdrawn {
+            return Err("You have already applied to this bounty".to_string());
         }
-    });
+    }
+    let application = Application {
+        bounty_id: request.bounty_id,
+        applicant: caller,
+        pitch: request.pitch,
+        status: ApplicationStatus::Pending,
+        applied_at: ic_cdk::api::time(),
+        decided_at: None,
+    };
+    APPLICATIONS.with(|apps| apps.borrow_mut().insert(key, application));
+    if previous.is_none() {
+        USER_APPLICATIONS.with(|user_apps| {
+            let mut ua = user_apps.borrow_mut();
+            let mut app_list = ua.get(&caller).unwrap_or(BountyIdList(vec![]));
+            app_list.0.push(request.bounty_id);
+            ua.insert(caller, app_list);
+        });
+    }
     Ok(())
 }
 
-#[query]
-pub fn get_bounty(bounty_id: u64) -> Option<Bounty> {
-    BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id).map(|x| x.clone()))
+/// Approve or decline a pending application. Requires the ReviewSubmissions permission.
+#[update]
+pub fn review_application(bounty_id: u64, applicant: Principal, approve: bool) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    authorize(&bounty, caller, Permission::ReviewSubmissions)?;
+    let key = (bounty_id, applicant);
+    let mut application = APPLICATIONS.with(|apps| apps.borrow().get(&key)).ok_or("Application not found")?;
+    if application.status != ApplicationStatus::Pending {
+        return Err("Application has already been decided".to_string());
+    }
+    application.status = if approve { ApplicationStatus::Approved } else { ApplicationStatus::Declined };
+    application.decided_at = Some(ic_cdk::api::time());
+    APPLICATIONS.with(|apps| apps.borrow_mut().insert(key, application));
+    Ok(())
 }
 
-#[query]
-pub fn get_all_bounties() -> Vec<Bounty> {
-    BOUNTIES.with(|bounties| {
-        bounties.borrow().iter().map(|(_, b)| b.clone()).collect()
+/// Withdraw the caller's pending or approved application.
+#[update]
+pub fn withdraw_application(bounty_id: u64) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let key = (bounty_id, caller);
+    let mut application = APPLICATIONS.with(|apps| apps.borrow().get(&key)).ok_or("Application not found")?;
+    if application.status != ApplicationStatus::Pending && application.status != ApplicationStatus::Approved {
+        return Err("Application can no longer be withdrawn".to_string());
+    }
+    application.status = ApplicationStatus::Withdrawn;
+    application.decided_at = Some(ic_cdk::api::time());
+    APPLICATIONS.with(|apps| apps.borrow_mut().insert(key, application));
+    Ok(())
+}
+
+/// List all applications to a bounty, in applicant order.
+#[query]
+pub fn get_bounty_applications(bounty_id: u64) -> Vec<Application> {
+    APPLICATIONS.with(|apps| {
+        apps.borrow()
+            .range((bounty_id, Principal::management_canister())..)
+            .take_while(|((id, _), _)| *id == bounty_id)
+            .map(|(_, a)| a)
+            .collect()
     })
 }
 
+/// List every application a user has made.
 #[query]
-pub fn get_active_bounties() -> Vec<Bounty> {
-    BOUNTIES.with(|bounties| {
-        bounties.borrow()
-            .iter()
-            .filter(|(_, b)| b.status == BountyStatus::Active)
-            .map(|(_, b)| b.clone())
+pub fn get_user_applications(user: Principal) -> Vec<Application> {
+    USER_APPLICATIONS.with(|user_apps| {
+        if let Some(bounty_list) = user_apps.borrow().get(&user) {
+            APPLICATIONS.with(|apps| {
+                let apps_ref = apps.borrow();
+                bounty_list.0.iter()
+                    .filter_map(|id| apps_ref.get(&(*id, user)))
+                    .collect()
+            })
+        } else {
+            vec![]
+        }
+    })
+}
+
+/// Edit a bounty's title, description or deadline. Requires the EditDetails permission.
+#[update]
+pub fn edit_bounty(request: UpdateBountyRequest) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let mut bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&request.bounty_id)
+    }).ok_or("Bounty not found")?;
+    check_expired_and_update!(bounty, request.bounty_id);
+    authorize(&bounty, caller, Permission::EditDetails)?;
+    if bounty.status != BountyStatus::Active && bounty.status != BountyStatus::PendingFunding {
+        return Err("Only pending or active bounties can be edited".to_string());
+    }
+    if let Some(title) = request.title {
+        if title.is_empty() {
+            return Err("Title cannot be empty".to_string());
+        }
+        bounty.title = title;
+    }
+    if let Some(description) = request.description {
+        bounty.description = description;
+    }
+    if let Some(deadline) = request.deadline {
+        if deadline <= ic_cdk::api::time() {
+            return Err("Deadline must be in the future".to_string());
+        }
+        bounty.deadline = Some(deadline);
+    }
+    let new_labels = request.labels.map(labels::normalize).transpose()?;
+    if let Some(requirement) = request.pr_requirement {
+        if github::parse_issue(&bounty.github_issue_url).is_err() {
+            return Err("PR requirements need a github.com issue URL".to_string());
+        }
+        bounty.pr_requirement = Some(requirement);
+    }
+    // Everything is validated; errors past this point would leave the label index stale
+    if let Some(new_labels) = new_labels {
+        if let Some(old_labels) = &bounty.labels {
+            labels::unindex(request.bounty_id, old_labels);
+        }
+        labels::index(request.bounty_id, &new_labels);
+        bounty.labels = Some(new_labels);
+    }
+    save_bounty(bounty);
+    Ok(())
+}
+
+/// Grant a co-maintainer a set of permissions on a bounty, replacing any previous grant.
+/// Only the creator, or an Owner or Admin of the bounty's organization, may do this.
+#[update]
+pub fn add_bounty_maintainer(bounty_id: u64, maintainer: Principal, permissions: Vec<Permission>) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    authorize_maintainers(&bounty, caller)?;
+    if maintainer == bounty.creator {
+        return Err("Bounty creator already holds every permission".to_string());
+    }
+    if permissions.is_empty() {
+        return Err("At least one permission is required".to_string());
+    }
+    let mut unique: Vec<Permission> = Vec::with_capacity(permissions.len());
+    for permission in permissions {
+        if !unique.contains(&permission) {
+            unique.push(permission);
+        }
+    }
+    MAINTAINERS.with(|maintainers| {
+        maintainers.borrow_mut().insert((bounty_id, maintainer), Maintainer {
+            bounty_id,
+            principal: maintainer,
+            permissions: unique,
+            added_by: caller,
+            added_at: ic_cdk::api::time(),
+        });
+    });
+    Ok(())
+}
+
+/// Revoke all of a co-maintainer's permissions on a bounty.
+#[update]
+pub fn remove_bounty_maintainer(bounty_id: u64, maintainer: Principal) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    // Maintainers may always step down themselves
+    if maintainer != caller {
+        authorize_maintainers(&bounty, caller)?;
+    }
+    MAINTAINERS.with(|maintainers| maintainers.borrow_mut().remove(&(bounty_id, maintainer)))
+        .ok_or("Principal is not a maintainer of this bounty")?;
+    Ok(())
+}
+
+/// List the co-maintainers of a bounty.
+#[query]
+pub fn get_bounty_maintainers(bounty_id: u64) -> Vec<Maintainer> {
+    MAINTAINERS.with(|maintainers| {
+        maintainers.borrow()
+            .range((bounty_id, Principal::management_canister())..)
+            .take_while(|((id, _), _)| *id == bounty_id)
+            .map(|(_, m)| m)
             .collect()
     })
 }
 
+#[update]
+pub async fn select_winner(bounty_id: u64, winner: Principal) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    check_expired_and_update!(bounty, bounty_id);
+    authorize(&bounty, caller, Permission::SelectWinner)?;
+    if bounty.status != BountyStatus::Active {
+        return Err("Bounty is not active".to_string());
+    }
+    if winner == caller && caller != bounty.creator {
+        return Err("Maintainers cannot select themselves as the winner".to_string());
+    }
+    // Wins count on the leaderboard, so they only go to a proven GitHub identity
+    let verified_login = USER_PROFILES.with(|profiles| profiles.borrow().get(&winner))
+        .and_then(|p| p.github_verified)
+        .map(|v| v.login)
+        .ok_or("Winner has not verified a GitHub account")?;
+    let authored_by_winner = |verification: &PrVerification| {
+        verification.author.as_ref().is_some_and(|author| verified_login.eq_ignore_ascii_case(author))
+    };
+    let submission_id = submissions::id_of(bounty_id, winner).ok_or("Selected winner has not submitted a solution")?;
+    // A cancellation refund may be in flight for this escrow
+    let _guard = lifecycle::PayoutGuard::acquire(bounty_id)?;
+    let bounty = match bounty.pr_requirement.clone() {
+        Some(requirement) => {
+            let verification = verify_submission_pr(&bounty, submission_id).await?;
+            if let Some(reason) = verification.shortfall(&requirement) {
+                return Err(format!("Winning PR does not meet the bounty's requirement: {}", reason));
+            }
+            if !authored_by_winner(&verification) {
+                return Err("Winner has not verified the GitHub account that authored the PR".to_string());
+            }
+            // Re-read: the record may have changed during the outcall
+            let bounty = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)).ok_or("Bounty not found")?;
+            if bounty.status != BountyStatus::Active {
+                return Err("Bounty is not active".to_string());
+            }
+            bounty
+        }
+        None => {
+            // No PR check is required, but a recorded one must not name someone else
+            let verification = submissions::get(bounty_id, submission_id).and_then(|s| s.verification);
+            if verification.is_some_and(|v| v.author.is_some() && !authored_by_winner(&v)) {
+                return Err("Winner has not verified the GitHub account that authored the PR".to_string());
+            }
+            bounty
+        }
+    };
+    // Update bounty
+    let mut bounty = bounty;
+    bounty.winner = Some(winner);
+    lifecycle::transition(bounty, BountyStatus::Completed, caller)?;
+    // Increment bounties_won in winner's profile
+    let mut profile = USER_PROFILES.with(|profiles| profiles.borrow().get(&winner)).unwrap_or_default();
+    profile.bounties_won += 1;
+    save_profile(winner, profile);
+    Ok(())
+}
+
+// Fetch the submission's PR from GitHub and record the result, reusing a
+// check made in the last few minutes. Every attempt counts towards that
+// cooldown, failed ones and ones still in flight included, so each submission
+// costs at most one outcall per PR_RECHECK_NS.
+async fn verify_submission_pr(bounty: &Bounty, submission_id: u64) -> Result<PrVerification, String> {
+    let submission = submissions::get(bounty.id, submission_id).ok_or("Submission not found")?;
+    let now = ic_cdk::api::time();
+    if let Some(previous) = &submission.verification {
+        // A webhook result only tells us the PR changed; it is never reused
+        let from_api = previous.source.as_ref().is_none_or(|s| *s == VerificationSource::GithubApi);
+        if from_api && now.saturating_sub(previous.checked_at) < PR_RECHECK_NS {
+            return Ok(previous.clone());
+        }
+    }
+    let mut attempt = submission.verification.unwrap_or(PrVerification {
+        checked_at: now,
+        merged: false,
+        closed: None,
+        same_repo: false,
+        references_issue: false,
+        author: None,
+        error: None,
+        source: None,
+    });
+    attempt.checked_at = now;
+    attempt.source = Some(VerificationSource::GithubApi);
+    attempt.error = Some("A GitHub check is in progress".to_string());
+    submissions::set_verification(bounty.id, submission_id, attempt.clone());
+    match github::verify_pull(&bounty.github_issue_url, &submission.pr_url).await {
+        Ok(verification) => {
+            submissions::set_verification(bounty.id, submission_id, verification.clone());
+            Ok(verification)
+        }
+        Err(e) => {
+            attempt.error = Some(e.clone());
+            submissions::set_verification(bounty.id, submission_id, attempt);
+            Err(e)
+        }
+    }
+}
+
+const PR_RECHECK_NS: u64 = 5 * 60 * 1_000_000_000;
+
+/// Check a submission's PR against GitHub: that it exists, is merged into the
+/// bounty's repository and references the issue. Open to the submitter and to
+/// anyone who may review submissions.
+#[update]
+pub async fn verify_submission(bounty_id: u64, submission_id: u64) -> Result<PrVerification, String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)).ok_or("Bounty not found")?;
+    let submission = submissions::get(bounty_id, submission_id).ok_or("Submission not found")?;
+    if submission.submitter != caller {
+        authorize(&bounty, caller, Permission::ReviewSubmissions)?;
+    }
+    verify_submission_pr(&bounty, submission_id).await
+}
+
+#[query]
+fn transform_github_response(args: ic_cdk::api::management_canister::http_request::TransformArgs) -> ic_cdk::api::management_canister::http_request::HttpResponse {
+    github::transform(args)
+}
+
+#[update]
+pub async fn claim_reward(bounty_id: u64) -> Result<Nat, String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let claim_key = ClaimKey {
+        bounty_id,
+        principal: caller,
+    };
+    if CLAIMS.with(|claims| claims.borrow().get(&claim_key)).is_some() {
+        return Err("Reward already claimed".to_string());
+    }
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    check_expired_and_update!(bounty, bounty_id);
+    match bounty.winner {
+        Some(winner) if winner == caller => {},
+        _ => return Err("You are not the winner of this bounty".to_string()),
+    }
+    if bounty.status == BountyStatus::Paid {
+        return Err("Reward already claimed".to_string());
+    }
+    if bounty.status != BountyStatus::Completed {
+        return Err("Bounty is not completed".to_string());
+    }
+    let _guard = lifecycle::PayoutGuard::acquire(bounty_id)?;
+    let transfer_result = escrow::transfer_from_escrow(
+        bounty.token_ledger,
+        bounty.escrow_subaccount,
+        caller,
+        bounty.prize_amount.clone()
+    ).await?;
+    CLAIMS.with(|claims| {
+        claims.borrow_mut().insert(claim_key, true);
+    });
+    events::record(caller, Some(bounty_id), EventKind::RewardClaimed { winner: caller, amount: bounty.prize_amount.clone() });
+    if let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) {
+        lifecycle::transition(bounty, BountyStatus::Paid, caller)?;
+    }
+    Ok(transfer_result)
+}
+
+#[update]
+pub async fn refund_expired_bounty(bounty_id: u64) -> Result<Nat, String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    authorize(&bounty, caller, Permission::Cancel)?;
+    if bounty.status == BountyStatus::Refunded {
+        return Err("Bounty has already been refunded".to_string());
+    }
+    if bounty.status != BountyStatus::Expired {
+        return Err("Bounty is not expired; cannot refund unless status is Expired".to_string());
+    }
+    if bounty.winner.is_some() {
+        return Err("Winner already selected, cannot refund".to_string());
+    }
+    let _guard = lifecycle::PayoutGuard::acquire(bounty_id)?;
+    let refund_result = escrow::transfer_from_escrow(
+        bounty.token_ledger,
+        bounty.escrow_subaccount,
+        bounty.creator, // refunds always return to the funder
+        bounty.prize_amount.clone()
+    ).await?;
+    events::record(caller, Some(bounty_id), EventKind::BountyRefunded { to: bounty.creator, amount: bounty.prize_amount.clone() });
+    if let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) {
+        lifecycle::transition(bounty, BountyStatus::Refunded, caller)?;
+    }
+    Ok(refund_result)
+}
+
+#[update]
+pub async fn cancel_bounty(bounty_id: u64) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let bounty = BOUNTIES.with(|bounties| {
+        bounties.borrow().get(&bounty_id)
+    }).ok_or("Bounty not found")?;
+    check_expired_and_update!(bounty, bounty_id);
+    authorize(&bounty, caller, Permission::Cancel)?;
+    if bounty.status == BountyStatus::PendingFunding || bounty.status == BountyStatus::Cancelled {
+        // Never verified as funded, so return whatever has been deposited.
+        // Cancelled bounties may be called again to sweep deposits that arrive late.
+        let _guard = lifecycle::PayoutGuard::acquire(bounty_id)?;
+        if bounty.status == BountyStatus::PendingFunding {
+            lifecycle::transition(bounty.clone(), BountyStatus::Cancelled, caller)?;
+        }
+        let amount = escrow::sweep(bounty.token_ledger, bounty.escrow_subaccount, bounty.creator).await?;
+        if amount > 0u64 {
+            events::record(caller, Some(bounty_id), EventKind::BountyRefunded { to: bounty.creator, amount });
+        }
+        return Ok(());
+    }
+    if !lifecycle::is_allowed(&bounty.status, &BountyStatus::Cancelled) {
+        return Err(format!("Cannot cancel a bounty that is {:?}", bounty.status));
+    }
+    if submissions::count(bounty_id) > 0 {
+        return Err("Cannot cancel bounty with submissions".to_string());
+    }
+    let _guard = lifecycle::PayoutGuard::acquire(bounty_id)?;
+    let _refund_result = escrow::transfer_from_escrow(
+        bounty.token_ledger,
+        bounty.escrow_subaccount,
+        bounty.creator, // refunds always return to the funder
+        bounty.prize_amount.clone()
+    ).await?;
+    events::record(caller, Some(bounty_id), EventKind::BountyRefunded { to: bounty.creator, amount: bounty.prize_amount.clone() });
+    // Re-read: the record may have changed while the transfer was in flight
+    if let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) {
+        lifecycle::transition(bounty, BountyStatus::Cancelled, caller)?;
+    }
+    Ok(())
+}
+
+#[query]
+pub fn get_bounty(bounty_id: u64) -> Option<Bounty> {
+    BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id))
+}
+
+/// Every status change a bounty has gone through, oldest first.
+#[query]
+pub fn get_bounty_status_history(bounty_id: u64) -> Vec<StatusTransition> {
+    lifecycle::history(bounty_id)
+}
+
+#[query]
+pub fn get_all_bounties() -> Vec<Bounty> {
+    BOUNTIES.with(|bounties| {
+        bounties.borrow().iter().map(|(_, b)| b.clone()).collect()
+    })
+}
+
+#[query]
+pub fn get_active_bounties() -> Vec<Bounty> {
+    indices::load(indices::ids_by_status(&BountyStatus::Active, 0, u64::MAX))
+}
+
 #[query]
 pub fn get_user_created_bounties(user: Principal) -> Vec<Bounty> {
-    USER_CREATED_BOUNTIES.with(|user_bounties| {
-        if let Some(bounty_list) = user_bounties.borrow().get(&user) {
+    indices::load(indices::ids_by_creator(user, 0, u64::MAX))
+}
+
+/// Submissions on a bounty, oldest first. Pass the returned `next_cursor` as
+/// `start_after` to fetch the following page.
+#[query]
+pub fn get_bounty_submissions(bounty_id: u64, start_after: Option<u64>, limit: u64) -> SubmissionPage {
+    let limit = limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    let (submissions, next_cursor) = submissions::page(bounty_id, start_after, limit);
+    SubmissionPage { submissions, next_cursor }
+}
+
+#[query]
+pub fn get_submission(bounty_id: u64, submission_id: u64) -> Option<Submission> {
+    submissions::get(bounty_id, submission_id)
+}
+
+#[query]
+pub fn get_submission_count(bounty_id: u64) -> u64 {
+    submissions::count(bounty_id)
+}
+
+#[query]
+pub fn get_user_submissions(user: Principal) -> Vec<Bounty> {
+    USER_SUBMISSIONS.with(|user_subs| {
+        if let Some(bounty_list) = user_subs.borrow().get(&user) {
+            BOUNTIES.with(|bounties| {
+                let bounties_ref = bounties.borrow();
+                bounty_list.0.iter()
+                    .filter_map(|id| bounties_ref.get(id))
+                    .collect()
+            })
+        } else {
+            vec![]
+        }
+    })
+}
+
+fn validate_org_handle(handle: &str) -> Result<(), String> {
+    let valid_chars = handle.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if handle.len() < 2 || handle.len() > 39 || !valid_chars || handle.starts_with('-') || handle.ends_with('-') {
+        return Err("Handle must be 2-39 lowercase letters, digits or inner hyphens".to_string());
+    }
+    Ok(())
+}
+
+fn insert_org_member(org_id: u64, principal: Principal, role: OrgRole, added_by: Principal) {
+    ORG_MEMBERS.with(|members| {
+        members.borrow_mut().insert((org_id, principal), OrgMember {
+            org_id,
+            principal,
+            role,
+            added_by,
+            added_at: ic_cdk::api::time(),
+        });
+    });
+    USER_ORGS.with(|user_orgs| user_orgs.borrow_mut().insert((principal, org_id), ()));
+}
+
+fn org_owner_count(org_id: u64) -> usize {
+    get_org_members(org_id).iter().filter(|m| m.role == OrgRole::Owner).count()
+}
+
+/// Create an organization. The caller becomes its first Owner.
+#[update]
+pub fn create_organization(request: CreateOrganizationRequest) -> Result<u64, String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    validate_org_handle(&request.handle)?;
+    if request.name.trim().is_empty() {
+        return Err("Organization name cannot be empty".to_string());
+    }
+    if ORG_HANDLES.with(|handles| handles.borrow().contains_key(&request.handle)) {
+        return Err("Organization handle is already taken".to_string());
+    }
+    let org_id = ORG_COUNTER.with(|counter| {
+        let mut counter_ref = counter.borrow_mut();
+        let new_id = *counter_ref.get() + 1;
+        counter_ref.set(new_id).expect("Failed to update counter");
+        new_id
+    });
+    let org = Organization {
+        id: org_id,
+        handle: request.handle.clone(),
+        name: request.name,
+        description: request.description,
+        website: request.website,
+        github_org: request.github_org,
+        avatar_url: request.avatar_url,
+        created_by: caller,
+        created_at: ic_cdk::api::time(),
+    };
+    ORGANIZATIONS.with(|orgs| orgs.borrow_mut().insert(org_id, org));
+    ORG_HANDLES.with(|handles| handles.borrow_mut().insert(request.handle, org_id));
+    insert_org_member(org_id, caller, OrgRole::Owner, caller);
+    Ok(org_id)
+}
+
+/// Update an organization's public profile. Owners and Admins only.
+#[update]
+pub fn update_organization_profile(org_id: u64, request: UpdateOrganizationRequest) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let mut org = ORGANIZATIONS.with(|orgs| orgs.borrow().get(&org_id)).ok_or("Organization not found")?;
+    authorize_org(org_id, caller, &[OrgRole::Owner, OrgRole::Admin])?;
+    if let Some(name) = request.name {
+        if name.trim().is_empty() {
+            return Err("Organization name cannot be empty".to_string());
+        }
+        org.name = name;
+    }
+    if let Some(description) = request.description { org.description = Some(description); }
+    if let Some(website) = request.website { org.website = Some(website); }
+    if let Some(github_org) = request.github_org { org.github_org = Some(github_org); }
+    if let Some(avatar_url) = request.avatar_url { org.avatar_url = Some(avatar_url); }
+    ORGANIZATIONS.with(|orgs| orgs.borrow_mut().insert(org_id, org));
+    Ok(())
+}
+
+/// Add a member or change their role. Only Owners may grant or revoke the Owner role;
+/// Admins may manage Admins and Reviewers.
+#[update]
+pub fn set_org_member(org_id: u64, member: Principal, role: OrgRole) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    if !ORGANIZATIONS.with(|orgs| orgs.borrow().contains_key(&org_id)) {
+        return Err("Organization not found".to_string());
+    }
+    let caller_role = authorize_org(org_id, caller, &[OrgRole::Owner, OrgRole::Admin])?;
+    let current_role = org_role(org_id, member);
+    let touches_owner = role == OrgRole::Owner || current_role == Some(OrgRole::Owner);
+    if touches_owner && caller_role != OrgRole::Owner {
+        return Err("Only owners can grant or revoke the Owner role".to_string());
+    }
+    if current_role == Some(OrgRole::Owner) && role != OrgRole::Owner && org_owner_count(org_id) <= 1 {
+        return Err("An organization must keep at least one owner".to_string());
+    }
+    insert_org_member(org_id, member, role, caller);
+    Ok(())
+}
+
+/// Remove a member. Members may always leave; removing others follows `set_org_member` rules.
+#[update]
+pub fn remove_org_member(org_id: u64, member: Principal) -> Result<(), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    let current_role = org_role(org_id, member).ok_or("Principal is not a member of this organization")?;
+    if member != caller {
+        let caller_role = authorize_org(org_id, caller, &[OrgRole::Owner, OrgRole::Admin])?;
+        if current_role == OrgRole::Owner && caller_role != OrgRole::Owner {
+            return Err("Only owners can remove an owner".to_string());
+        }
+    }
+    if current_role == OrgRole::Owner && org_owner_count(org_id) <= 1 {
+        return Err("An organization must keep at least one owner".to_string());
+    }
+    ORG_MEMBERS.with(|members| members.borrow_mut().remove(&(org_id, member)));
+    USER_ORGS.with(|user_orgs| user_orgs.borrow_mut().remove(&(member, org_id)));
+    Ok(())
+}
+
+#[query]
+pub fn get_organization(org_id: u64) -> Option<Organization> {
+    ORGANIZATIONS.with(|orgs| orgs.borrow().get(&org_id))
+}
+
+#[query]
+pub fn get_organization_by_handle(handle: String) -> Option<Organization> {
+    ORG_HANDLES.with(|handles| handles.borrow().get(&handle)).and_then(get_organization)
+}
+
+#[query]
+pub fn get_org_members(org_id: u64) -> Vec<OrgMember> {
+    ORG_MEMBERS.with(|members| {
+        members.borrow()
+            .range((org_id, Principal::management_canister())..)
+            .take_while(|((id, _), _)| *id == org_id)
+            .map(|(_, m)| m)
+            .collect()
+    })
+}
+
+/// Organizations a user belongs to.
+#[query]
+pub fn get_user_organizations(user: Principal) -> Vec<Organization> {
+    let org_ids: Vec<u64> = USER_ORGS.with(|user_orgs| {
+        user_orgs.borrow()
+            .range((user, 0)..)
+            .take_while(|((p, _), _)| *p == user)
+            .map(|((_, org_id), _)| org_id)
+            .collect()
+    });
+    org_ids.into_iter().filter_map(get_organization).collect()
+}
+
+/// Organization counterpart of `get_user_created_bounties`.
+#[query]
+pub fn get_org_bounties(org_id: u64) -> Vec<Bounty> {
+    ORG_BOUNTIES.with(|org_bounties| {
+        if let Some(bounty_list) = org_bounties.borrow().get(&org_id) {
             BOUNTIES.with(|bounties| {
                 let bounties_ref = bounties.borrow();
                 bounty_list.0.iter()
-                    .filter_map(|id| bounties_ref.get(id).map(|x| x.clone()))
+                    .filter_map(|id| bounties_ref.get(id))
                     .collect()
             })
         } else {
@@ -410,14 +1406,117 @@ pub fn get_user_created_bounties(user: Principal) -> Vec<Bounty> {
     })
 }
 
+// Shortest allowed gap between two bounties of a series
+const MIN_SERIES_INTERVAL_NS: u64 = 60 * 60 * 1_000_000_000;
+
+/// Define a recurring bounty series. Returns the series id and the budget account the
+/// creator prefunds; each run moves one prize plus the payout fee from the budget into a
+/// fresh bounty escrow, and pays the fee for that transfer too.
+#[update]
+pub fn create_recurring_series(request: CreateRecurringSeriesRequest) -> Result<(u64, String), String> {
+    update_last_updated();
+    let caller = ic_cdk::caller();
+    if request.interval_ns < MIN_SERIES_INTERVAL_NS {
+        return Err(format!("Interval must be at least {} nanoseconds", MIN_SERIES_INTERVAL_NS));
+    }
+    let now = ic_cdk::api::time();
+    let series_id = SERIES_COUNTER.with(|counter| {
+        let mut counter_ref = counter.borrow_mut();
+        let new_id = *counter_ref.get() + 1;
+        counter_ref.set(new_id).expect("Failed to update counter");
+        new_id
+    });
+    let budget_subaccount = escrow::generate_series_subaccount(series_id);
+    let new_series = RecurringSeries {
+        id: series_id,
+        creator: caller,
+        org_id: request.org_id,
+        template: request.template,
+        interval_ns: request.interval_ns,
+        next_run_at: request.first_run_at.unwrap_or(now).max(now),
+        status: SeriesStatus::Active,
+        budget_subaccount,
+        spawned_count: 0,
+        created_at: now,
+        last_error: None,
+    };
+    // Spawned bounties follow create_bounty rules, so reject bad templates up front
+    validate_bounty_request(caller, &series::spawn_request(&new_series, now))?;
+    series::save_series(new_series);
+    USER_SERIES.with(|user_series| user_series.borrow_mut().insert((caller, series_id), ()));
+    Ok((series_id, escrow::get_escrow_account(budget_subaccount).to_string()))
+}
+
+/// Stop spawning bounties until the series is resumed.
+#[update]
+pub fn pause_series(series_id: u64) -> Result<(), String> {
+    update_last_updated();
+    let mut target = series::get_series(series_id).ok_or("Series not found")?;
+    authorize_series(&target, ic_cdk::caller())?;
+    if target.status != SeriesStatus::Active {
+        return Err("Series is not active".to_string());
+    }
+    target.status = SeriesStatus::Paused;
+    series::save_series(target);
+    Ok(())
+}
+
+/// Resume a paused series. A run that fell due while paused happens on the next tick.
+#[update]
+pub fn resume_series(series_id: u64) -> Result<(), String> {
+    update_last_updated();
+    let mut target = series::get_series(series_id).ok_or("Series not found")?;
+    authorize_series(&target, ic_cdk::caller())?;
+    if target.status != SeriesStatus::Paused {
+        return Err("Series is not paused".to_string());
+    }
+    target.status = SeriesStatus::Active;
+    target.last_error = None;
+    series::save_series(target);
+    Ok(())
+}
+
+/// Cancel a series for good and refund its unspent budget, less the ledger fee, to the creator.
+/// Bounties it already spawned are unaffected.
+#[update]
+pub async fn cancel_series(series_id: u64) -> Result<Nat, String> {
+    update_last_updated();
+    let mut target = series::get_series(series_id).ok_or("Series not found")?;
+    authorize_series(&target, ic_cdk::caller())?;
+    if target.status != SeriesStatus::Cancelled {
+        target.status = SeriesStatus::Cancelled;
+        series::save_series(target.clone());
+    }
+    // Cancelled series may be called again to sweep deposits that arrive late
+    series::refund_budget(&target, target.creator).await
+}
+
 #[query]
-pub fn get_user_submissions(user: Principal) -> Vec<Bounty> {
-    USER_SUBMISSIONS.with(|user_subs| {
-        if let Some(bounty_list) = user_subs.borrow().get(&user) {
+pub fn get_series(series_id: u64) -> Option<RecurringSeries> {
+    series::get_series(series_id)
+}
+
+#[query]
+pub fn get_user_series(user: Principal) -> Vec<RecurringSeries> {
+    let series_ids: Vec<u64> = USER_SERIES.with(|user_series| {
+        user_series.borrow()
+            .range((user, 0)..)
+            .take_while(|((p, _), _)| *p == user)
+            .map(|((_, series_id), _)| series_id)
+            .collect()
+    });
+    series_ids.into_iter().filter_map(series::get_series).collect()
+}
+
+/// Bounties spawned by a series, oldest first.
+#[query]
+pub fn get_series_bounties(series_id: u64) -> Vec<Bounty> {
+    SERIES_BOUNTIES.with(|series_bounties| {
+        if let Some(bounty_list) = series_bounties.borrow().get(&series_id) {
             BOUNTIES.with(|bounties| {
                 let bounties_ref = bounties.borrow();
                 bounty_list.0.iter()
-                    .filter_map(|id| bounties_ref.get(id).map(|x| x.clone()))
+                    .filter_map(|id| bounties_ref.get(id))
                     .collect()
             })
         } else {
@@ -428,33 +1527,278 @@ pub fn get_user_submissions(user: Principal) -> Vec<Bounty> {
 
 #[query]
 pub fn get_bounties_paginated(offset: u64, limit: u64) -> Vec<Bounty> {
+    // Ids run 1..=counter with no gaps (bounties are never deleted), so the
+    // offset maps directly onto a key range
     BOUNTIES.with(|bounties| {
         bounties.borrow()
-            .iter()
-            .skip(offset as usize)
+            .range(offset.saturating_add(1)..)
             .take(limit as usize)
-            .map(|(_, b)| b.clone())
+            .map(|(_, b)| b)
             .collect()
     })
 }
 
 #[query]
 pub fn get_bounties_by_status(status: BountyStatus, offset: u64, limit: u64) -> Vec<Bounty> {
-    BOUNTIES.with(|bounties| {
-        bounties.borrow()
-            .iter()
-            .filter(|(_, b)| b.status == status)
+    indices::load(indices::ids_by_status(&status, offset, limit))
+}
+
+#[query]
+pub fn get_bounties_by_ledger(token_ledger: Principal, offset: u64, limit: u64) -> Vec<Bounty> {
+    indices::load(indices::ids_by_ledger(token_ledger, offset, limit))
+}
+
+/// Every bounty funding a GitHub issue, stacked ones included, oldest first.
+/// The URL may be in any form `create_bounty` accepts.
+#[query]
+pub fn get_bounties_by_issue(github_issue_url: String) -> Result<Vec<Bounty>, String> {
+    let issue = github::parse_issue(&github_issue_url)?;
+    Ok(indices::load(indices::ids_by_issue(&issue)))
+}
+
+/// The canonical form `create_bounty` would store a GitHub issue URL in.
+#[query]
+pub fn parse_github_issue_url(github_issue_url: String) -> Result<GithubIssueRef, String> {
+    github::parse_issue(&github_issue_url)
+}
+
+const DEFAULT_TOP_CONTRIBUTORS: u64 = 10;
+const MAX_TOP_CONTRIBUTORS: u64 = 100;
+
+/// Bounties posted against a repository ("owner/repo"), in issue order, at most
+/// MAX_PAGE_SIZE at a time.
+#[query]
+pub fn get_repo_bounties(repo: String, open_only: bool, offset: u64, limit: u64) -> Result<Vec<BountySummary>, String> {
+    let (owner, repo) = github::parse_repo(&repo)?;
+    let ids = repos::page_ids(&owner, &repo, open_only, offset, limit.clamp(1, MAX_PAGE_SIZE));
+    Ok(summarize_all(indices::load(ids)))
+}
+
+#[query]
+pub fn get_repo_stats(repo: String, top: Option<u64>) -> Result<RepoStats, String> {
+    let (owner, repo) = github::parse_repo(&repo)?;
+    Ok(repos::repo_stats(&owner, &repo, top.unwrap_or(DEFAULT_TOP_CONTRIBUTORS).min(MAX_TOP_CONTRIBUTORS) as usize))
+}
+
+/// Stats across every repository of a GitHub user or organization, with a breakdown per repository.
+#[query]
+pub fn get_owner_stats(owner: String, top: Option<u64>) -> Result<OwnerStats, String> {
+    github::check_owner(&owner)?;
+    Ok(repos::owner_stats(&owner.to_lowercase(), top.unwrap_or(DEFAULT_TOP_CONTRIBUTORS).min(MAX_TOP_CONTRIBUTORS) as usize))
+}
+
+/// Bounties whose deadline falls in `[from, to)`, soonest first.
+#[query]
+pub fn get_bounties_by_deadline(from: u64, to: u64, limit: u64) -> Vec<Bounty> {
+    indices::load(indices::ids_by_deadline(from, to, limit))
+}
+
+/// Bounties matching all label filters and the optional status, in ascending id order.
+/// Candidates come from the label and status indices; bounties are only loaded once they
+/// are known to match.
+#[query]
+pub fn get_bounties_filtered(filter: BountyFilter, offset: u64, limit: u64) -> Result<Vec<Bounty>, String> {
+    let hours_ok = |b: &Bounty| match filter.max_estimated_hours {
+        Some(max) => b.labels.as_ref().and_then(|l| l.estimated_hours).is_some_and(|h| h <= max),
+        None => true,
+    };
+    let candidate_ids: Box<dyn Iterator<Item = u64>> = match (labels::matching_bounty_ids(&filter.tags, &filter.languages, &filter.skills)?, &filter.status) {
+        (Some(ids), Some(status)) => Box::new(ids.into_iter().filter(move |id| indices::has_status(*id, status))),
+        (Some(ids), None) => Box::new(ids.into_iter()),
+        (None, Some(status)) => Box::new(indices::ids_by_status(status, 0, u64::MAX).into_iter()),
+        (None, None) => Box::new(1..=BOUNTY_COUNTER.with(|c| *c.borrow().get())),
+    };
+    let page = BOUNTIES.with(|bounties| {
+        let bounties_ref = bounties.borrow();
+        candidate_ids
+            .filter_map(|id| bounties_ref.get(&id))
+            .filter(|b| hours_ok(b))
             .skip(offset as usize)
             .take(limit as usize)
-            .map(|(_, b)| b.clone())
             .collect()
-    })
+    });
+    Ok(page)
+}
+
+const MAX_PAGE_SIZE: u64 = 100;
+
+/// Cursor-paginated listing in any sort order. Pages are anchored on the last bounty's
+/// sort key rather than an offset, so bounties changing status between calls never
+/// cause items to be skipped or repeated.
+#[query]
+pub fn list_bounties(query: BountyListQuery) -> Result<BountyPage, String> {
+    let after = query.cursor.as_deref().map(|c| listing::decode_cursor(c, query.sort)).transpose()?;
+    let limit = query.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    let mut bounties = Vec::with_capacity(limit);
+    let mut last_key = None;
+    let mut has_more = false;
+    BOUNTIES.with(|all| {
+        let all = all.borrow();
+        listing::walk(query.sort, query.status.as_ref(), query.direction == SortDirection::Descending, after, |key| {
+            if bounties.len() == limit {
+                has_more = true;
+                return false;
+            }
+            let Some(bounty) = all.get(&key.bounty_id) else { return true };
+            bounties.push(bounty);
+            last_key = Some(key.clone());
+            true
+        });
+    });
+    let next_cursor = if has_more { last_key.as_ref().map(listing::encode_cursor) } else { None };
+    Ok(BountyPage { bounties, next_cursor })
+}
+
+const SEARCH_PAGE_SIZE: usize = 20;
+
+/// Ranked full-text search over titles, descriptions and repository names, combined with
+/// the same filters as `get_bounties_filtered`. Pass the returned `next_cursor` to fetch
+/// the following page.
+#[query]
+pub fn search_bounties(query: String, filters: BountyFilter, cursor: Option<String>) -> Result<SearchPage, String> {
+    let after = cursor.as_deref().map(search::decode_cursor).transpose()?;
+    let label_ids = labels::matching_bounty_ids(&filters.tags, &filters.languages, &filters.skills)?;
+    let mut ranked = search::rank(&query, filters.status.as_ref(), label_ids.as_ref());
+    if let Some(max_hours) = filters.max_estimated_hours {
+        ranked.retain(|(_, id)| {
+            BOUNTIES.with(|bounties| bounties.borrow().get(id))
+                .and_then(|b| b.labels)
+                .and_then(|l| l.estimated_hours)
+                .is_some_and(|h| h <= max_hours)
+        });
+    }
+    let total_matches = ranked.len() as u64;
+    let start = search::resume_position(&ranked, after);
+    let page: Vec<(f64, u64)> = ranked.into_iter().skip(start).take(SEARCH_PAGE_SIZE).collect();
+    let next_cursor = if start as u64 + (page.len() as u64) < total_matches {
+        page.last().map(|(score, id)| search::encode_cursor(*score, *id))
+    } else {
+        None
+    };
+    let hits = BOUNTIES.with(|bounties| {
+        let bounties_ref = bounties.borrow();
+        page.into_iter()
+            .filter_map(|(score, id)| bounties_ref.get(&id).map(|bounty| SearchHit { bounty: summarize(bounty), score }))
+            .collect()
+    });
+    Ok(SearchPage { hits, total_matches, next_cursor })
+}
+
+fn summarize(bounty: Bounty) -> BountySummary {
+    BountySummary {
+        submission_count: submissions::count(bounty.id),
+        id: bounty.id,
+        title: bounty.title,
+        status: bounty.status,
+        prize_amount: bounty.prize_amount,
+        token_ledger: bounty.token_ledger,
+        deadline: bounty.deadline,
+        creator: bounty.creator,
+    }
+}
+
+fn summarize_all(bounties: Vec<Bounty>) -> Vec<BountySummary> {
+    bounties.into_iter().map(summarize).collect()
+}
+
+// Summary counterparts of the listing endpoints. They return the same bounties in the
+// same order, without descriptions, escrow details or other per-bounty detail.
+
+#[query]
+pub fn get_all_bounty_summaries() -> Vec<BountySummary> {
+    summarize_all(get_all_bounties())
+}
+
+#[query]
+pub fn get_active_bounty_summaries() -> Vec<BountySummary> {
+    summarize_all(get_active_bounties())
+}
+
+#[query]
+pub fn get_bounty_summaries_paginated(offset: u64, limit: u64) -> Vec<BountySummary> {
+    summarize_all(get_bounties_paginated(offset, limit))
+}
+
+#[query]
+pub fn get_bounty_summaries_by_status(status: BountyStatus, offset: u64, limit: u64) -> Vec<BountySummary> {
+    summarize_all(get_bounties_by_status(status, offset, limit))
+}
+
+#[query]
+pub fn get_user_created_bounty_summaries(user: Principal) -> Vec<BountySummary> {
+    summarize_all(get_user_created_bounties(user))
+}
+
+#[query]
+pub fn get_bounty_summaries_filtered(filter: BountyFilter, offset: u64, limit: u64) -> Result<Vec<BountySummary>, String> {
+    get_bounties_filtered(filter, offset, limit).map(summarize_all)
+}
+
+#[query]
+pub fn list_bounty_summaries(query: BountyListQuery) -> Result<BountySummaryPage, String> {
+    let page = list_bounties(query)?;
+    Ok(BountySummaryPage { bounties: summarize_all(page.bounties), next_cursor: page.next_cursor })
+}
+
+/// Distinct labels of a kind with how many bounties use each, for building filter menus.
+#[query]
+pub fn get_label_counts(kind: LabelKind) -> Vec<(String, u64)> {
+    labels::label_counts(kind)
+}
+
+/// Activity log entries, optionally limited to one bounty and/or principal.
+/// Pass the returned `next_index` as `start` to fetch the following page.
+#[query]
+pub fn get_events(query: EventQuery) -> EventPage {
+    let limit = query.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    events::page(&query, limit)
+}
+
+#[query]
+pub fn get_event_count() -> u64 {
+    events::len()
+}
+
+/// The caller's notifications, newest first. Pass the returned `next_before`
+/// as `before` to fetch the following page.
+#[query]
+pub fn get_notifications(query: NotificationQuery) -> NotificationPage {
+    let limit = query.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+    notifications::page(ic_cdk::caller(), &query, limit)
+}
+
+#[query]
+pub fn get_unread_notification_count() -> u64 {
+    notifications::unread_count(ic_cdk::caller())
+}
+
+/// Mark the caller's notifications read, or all of them when `ids` is None.
+/// Returns how many were unread.
+#[update]
+pub fn mark_notifications_read(ids: Option<Vec<u64>>) -> u64 {
+    update_last_updated();
+    notifications::mark_read(ic_cdk::caller(), ids)
+}
+
+/// Read-only JSON API for dashboards and scripts; see `http.rs` for the routes.
+#[query]
+pub fn http_request(request: HttpRequest) -> HttpResponse {
+    http::route(&request)
+}
+
+/// GitHub webhook deliveries and badges naming a token whose symbol is not
+/// cached yet, upgraded from `http_request`.
+#[update]
+pub async fn http_request_update(request: HttpRequest) -> HttpResponse {
+    update_last_updated();
+    http::route_update(&request).await
 }
 
 #[query]
 pub fn get_status() -> CanisterStatus {
-    let bounty_count = BOUNTIES.with(|b| b.borrow().len() as u64);
-    let last_updated = LAST_UPDATED.with(|cell| cell.get());
+    let bounty_count = BOUNTIES.with(|b| b.borrow().len());
+    // The heap cell resets on upgrade; the event log does not
+    let last_updated = LAST_UPDATED.with(|cell| cell.get()).max(events::last_timestamp().unwrap_or(0));
     CanisterStatus {
         version: CANISTER_VERSION.to_string(),
         bounty_count,
@@ -462,31 +1806,35 @@ pub fn get_status() -> CanisterStatus {
     }
 }
 
-#[query]
-pub fn get_top_creators(limit: u64) -> Vec<UserProfile> {
+// Profiles ranked by `metric`, optionally only those with a verified GitHub
+// account, so public rankings can be restricted to proven identities
+pub(crate) fn top_profiles(metric: fn(&UserProfile) -> u64, limit: u64, verified_only: bool) -> Vec<(Principal, UserProfile)> {
     USER_PROFILES.with(|profiles| {
-        let mut all: Vec<_> = profiles.borrow().values().collect();
-        all.sort_by(|a, b| b.bounties_posted.cmp(&a.bounties_posted));
+        let mut all: Vec<_> = profiles
+            .borrow()
+            .iter()
+            .filter(|(_, p)| !verified_only || p.github_verified.is_some())
+            .collect();
+        all.sort_by_key(|(_, p)| std::cmp::Reverse(metric(p)));
         all.into_iter().take(limit as usize).collect()
     })
 }
 
+/// Profiles by bounties posted. Each carries `github_verified` when its GitHub
+/// account is proven; `verified_only` drops the rest.
 #[query]
-pub fn get_top_winners(limit: u64) -> Vec<UserProfile> {
-    USER_PROFILES.with(|profiles| {
-        let mut all: Vec<_> = profiles.borrow().values().collect();
-        all.sort_by(|a, b| b.bounties_won.cmp(&a.bounties_won));
-        all.into_iter().take(limit as usize).collect()
-    })
+pub fn get_top_creators(limit: u64, verified_only: Option<bool>) -> Vec<UserProfile> {
+    top_profiles(|p| p.bounties_posted, limit, verified_only.unwrap_or(false)).into_iter().map(|(_, p)| p).collect()
 }
 
 #[query]
-pub fn get_top_participants(limit: u64) -> Vec<UserProfile> {
-    USER_PROFILES.with(|profiles| {
-        let mut all: Vec<_> = profiles.borrow().values().collect();
-        all.sort_by(|a, b| b.bounties_participated.cmp(&a.bounties_participated));
-        all.into_iter().take(limit as usize).collect()
-    })
+pub fn get_top_winners(limit: u64, verified_only: Option<bool>) -> Vec<UserProfile> {
+    top_profiles(|p| p.bounties_won, limit, verified_only.unwrap_or(false)).into_iter().map(|(_, p)| p).collect()
+}
+
+#[query]
+pub fn get_top_participants(limit: u64, verified_only: Option<bool>) -> Vec<UserProfile> {
+    top_profiles(|p| p.bounties_participated, limit, verified_only.unwrap_or(false)).into_iter().map(|(_, p)| p).collect()
 }
 
 ic_cdk::export_candid!();
@@ -0,0 +1,106 @@
+use candid::Principal;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use crate::state::{RESERVATIONS, STATUS_TRANSITIONS};
+use crate::types::{Bounty, BountyStatus, StatusTransition};
+
+use BountyStatus::*;
+
+// Every status change a bounty may make. Anything not listed is rejected.
+const ALLOWED: &[(BountyStatus, BountyStatus)] = &[
+    (PendingFunding, Active),    // escrow deposit verified, or funded by its series
+    (PendingFunding, Cancelled), // creator gives up before funding
+    (Active, Completed),         // winner selected
+    (Active, Cancelled),         // creator cancels and is refunded
+    (Active, Expired),           // deadline passed without a winner
+    (Completed, Paid),           // winner claimed the prize
+    (Expired, Refunded),         // prize returned to the creator
+];
+
+thread_local! {
+    // Bounties whose escrow is being transferred out right now
+    static PAYOUTS_IN_FLIGHT: RefCell<BTreeSet<u64>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+/// Held across the ledger call that empties a bounty's escrow, so a second
+/// claim or refund cannot start before the status reflects the first.
+pub struct PayoutGuard(u64);
+
+impl PayoutGuard {
+    pub fn acquire(bounty_id: u64) -> Result<Self, String> {
+        if PAYOUTS_IN_FLIGHT.with(|p| p.borrow_mut().insert(bounty_id)) {
+            Ok(PayoutGuard(bounty_id))
+        } else {
+            Err("A payout for this bounty is already in progress".to_string())
+        }
+    }
+
+    /// Whether a claim or refund of this bounty is in flight.
+    pub fn is_held(bounty_id: u64) -> bool {
+        PAYOUTS_IN_FLIGHT.with(|p| p.borrow().contains(&bounty_id))
+    }
+}
+
+impl Drop for PayoutGuard {
+    fn drop(&mut self) {
+        PAYOUTS_IN_FLIGHT.with(|p| p.borrow_mut().remove(&self.0));
+    }
+}
+
+pub fn is_allowed(from: &BountyStatus, to: &BountyStatus) -> bool {
+    ALLOWED.iter().any(|(f, t)| f == from && t == to)
+}
+
+/// Move `bounty` to `to`, save it and log the change against `actor`.
+/// The caller sets any other fields that change together with the status.
+pub fn transition(mut bounty: Bounty, to: BountyStatus, actor: Principal) -> Result<Bounty, String> {
+    if !is_allowed(&bounty.status, &to) {
+        return Err(format!("Illegal status transition: {:?} -> {:?}", bounty.status, to));
+    }
+    let from = std::mem::replace(&mut bounty.status, to.clone());
+    if to == Active {
+        bounty.funded_at = Some(ic_cdk::api::time());
+    }
+    crate::save_bounty(bounty.clone());
+    if from == Active {
+        // Reservations only mean something while the bounty takes submissions
+        RESERVATIONS.with(|r| r.borrow_mut().remove(&bounty.id));
+    }
+    log(bounty.id, Some(from), to, actor);
+    crate::events::record_status_change(&bounty, actor);
+    Ok(bounty)
+}
+
+/// Log the initial status of a newly created bounty.
+pub fn record_creation(bounty: &Bounty, actor: Principal) {
+    log(bounty.id, None, bounty.status.clone(), actor);
+}
+
+/// Status history of a bounty, oldest first.
+pub fn history(bounty_id: u64) -> Vec<StatusTransition> {
+    STATUS_TRANSITIONS.with(|log| {
+        log.borrow()
+            .range((bounty_id, 0)..=(bounty_id, u64::MAX))
+            .map(|(_, t)| t)
+            .collect()
+    })
+}
+
+fn log(bounty_id: u64, from: Option<BountyStatus>, to: BountyStatus, actor: Principal) {
+    STATUS_TRANSITIONS.with(|log| {
+        let mut log = log.borrow_mut();
+        let seq = log
+            .range((bounty_id, 0)..=(bounty_id, u64::MAX))
+            .next_back()
+            .map(|((_, seq), _)| seq + 1)
+            .unwrap_or(0);
+        log.insert((bounty_id, seq), StatusTransition {
+            bounty_id,
+            seq,
+            from,
+            to,
+            actor,
+            at: ic_cdk::api::time(),
+        });
+    });
+}
@@ -0,0 +1,343 @@
+use candid::{Nat, Principal};
+use std::ops::Bound::{Excluded, Included};
+use std::time::Duration;
+use crate::escrow;
+use crate::indices;
+use ic_stable_structures::{StableBTreeMap, Storable};
+use crate::state::{Memory, BOUNTIES, LEDGER_DECIMALS, SORT_INDEX, STATUS_SORT_INDEX};
+use crate::types::{Bounty, BountySort, BountyStatus, SortKey, StatusSortKey};
+
+const ALL_SORTS: [BountySort; 4] = [
+    BountySort::CreatedAt,
+    BountySort::PrizeAmount,
+    BountySort::Deadline,
+    BountySort::Submissions,
+];
+
+// Prizes are compared in a common 18-decimal unit so that tokens with different
+// precision sort by whole-token amount
+const NORMALIZED_DECIMALS: u32 = 18;
+// Most ICRC-1 ledgers (ICP, ckBTC) use 8 decimals; used until a ledger's value is known
+const DEFAULT_DECIMALS: u8 = 8;
+
+pub fn ledger_decimals(token_ledger: Principal) -> u8 {
+    LEDGER_DECIMALS.with(|d| d.borrow().get(&token_ledger)).unwrap_or(DEFAULT_DECIMALS)
+}
+
+pub fn normalize_amount(amount: &Nat, decimals: u8) -> u128 {
+    let raw = u128::try_from(amount.0.clone()).unwrap_or(u128::MAX);
+    let decimals = decimals as u32;
+    if decimals <= NORMALIZED_DECIMALS {
+        raw.saturating_mul(10u128.saturating_pow(NORMALIZED_DECIMALS - decimals))
+    } else {
+        raw / 10u128.saturating_pow(decimals - NORMALIZED_DECIMALS)
+    }
+}
+
+pub fn sort_value(sort: BountySort, bounty: &Bounty) -> u128 {
+    match sort {
+        BountySort::CreatedAt => bounty.created_at as u128,
+        BountySort::PrizeAmount => normalize_amount(&bounty.prize_amount, ledger_decimals(bounty.token_ledger)),
+        // Bounties without a deadline sort after every dated one
+        BountySort::Deadline => bounty.deadline.unwrap_or(u64::MAX) as u128,
+        BountySort::Submissions => crate::submissions::count(bounty.id) as u128,
+    }
+}
+
+fn sort_key(sort: BountySort, bounty: &Bounty) -> SortKey {
+    SortKey { sort, value: sort_value(sort, bounty), bounty_id: bounty.id }
+}
+
+fn status_sort_key(status: &BountyStatus, key: SortKey) -> StatusSortKey {
+    StatusSortKey { status: status.code(), key }
+}
+
+/// Bring the sort index in line with `bounty`, given the record it replaces.
+pub fn reindex(previous: Option<&Bounty>, bounty: &Bounty) {
+    SORT_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        for sort in ALL_SORTS {
+            // Prize and ledger never change after creation. Recomputing the prize key could
+            // also disagree with the stored one if the ledger's decimals were cached since;
+            // `resort_ledger` moves those keys instead.
+            // Submission counts live outside the bounty record; see `update_submission_count`.
+            if previous.is_some() && matches!(sort, BountySort::PrizeAmount | BountySort::Submissions) {
+                continue;
+            }
+            let key = sort_key(sort, bounty);
+            if let Some(previous) = previous {
+                let old_key = sort_key(sort, previous);
+                if old_key == key {
+                    continue;
+                }
+                idx.remove(&old_key);
+            }
+            idx.insert(key, ());
+        }
+    });
+    STATUS_SORT_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        for sort in ALL_SORTS {
+            // Prize and submission values are not changed by a save (see above), so
+            // for them only a status change moves the entry
+            let key = sort_key(sort, bounty);
+            let new_key = status_sort_key(&bounty.status, key.clone());
+            if let Some(previous) = previous {
+                let old_key = match sort {
+                    BountySort::PrizeAmount | BountySort::Submissions => status_sort_key(&previous.status, key),
+                    _ => status_sort_key(&previous.status, sort_key(sort, previous)),
+                };
+                if old_key == new_key {
+                    continue;
+                }
+                idx.remove(&old_key);
+            }
+            idx.insert(new_key, ());
+        }
+    });
+}
+
+/// Move a ledger's bounties to their true prize position once its decimals are
+/// cached. Until then their keys were computed with DEFAULT_DECIMALS.
+pub fn resort_ledger(token_ledger: Principal, decimals: u8) {
+    if decimals == DEFAULT_DECIMALS {
+        return;
+    }
+    SORT_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        for bounty in indices::load(indices::ids_by_ledger(token_ledger, 0, u64::MAX)) {
+            let key = |decimals| SortKey {
+                sort: BountySort::PrizeAmount,
+                value: normalize_amount(&bounty.prize_amount, decimals),
+                bounty_id: bounty.id,
+            };
+            if idx.remove(&key(DEFAULT_DECIMALS)).is_some() {
+                idx.insert(key(decimals), ());
+                STATUS_SORT_INDEX.with(|status_idx| {
+                    let mut status_idx = status_idx.borrow_mut();
+                    status_idx.remove(&status_sort_key(&bounty.status, key(DEFAULT_DECIMALS)));
+                    status_idx.insert(status_sort_key(&bounty.status, key(decimals)), ());
+                });
+            }
+        }
+    });
+}
+
+/// Fetch the decimals of ledgers that bounties from before decimals were cached
+/// use, so their prize keys get corrected. Runs after upgrade, outside post_upgrade
+/// since it needs inter-canister calls.
+pub fn start_decimals_refresh() {
+    ic_cdk_timers::set_timer(Duration::ZERO, || {
+        ic_cdk::spawn(async {
+            let unknown: Vec<Principal> = indices::ledgers()
+                .into_iter()
+                .filter(|ledger| LEDGER_DECIMALS.with(|d| !d.borrow().contains_key(ledger)))
+                .collect();
+            for ledger in unknown {
+                // A ledger that does not answer keeps the default; the next upgrade retries
+                let _ = escrow::token_decimals(ledger).await;
+            }
+        })
+    });
+}
+
+/// Move a bounty within the Submissions sort order after a submission is added.
+pub fn update_submission_count(bounty_id: u64, status: &BountyStatus, old_count: u64, new_count: u64) {
+    let old_key = SortKey { sort: BountySort::Submissions, value: old_count as u128, bounty_id };
+    let new_key = SortKey { sort: BountySort::Submissions, value: new_count as u128, bounty_id };
+    SORT_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        idx.remove(&old_key);
+        idx.insert(new_key.clone(), ());
+    });
+    STATUS_SORT_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        idx.remove(&status_sort_key(status, old_key));
+        idx.insert(status_sort_key(status, new_key), ());
+    });
+}
+
+/// Index bounties created before sorted listings, or their per-status copy,
+/// existed. Safe to call repeatedly.
+pub fn backfill() {
+    let indexed = SORT_INDEX.with(|idx| {
+        idx.borrow()
+            .range(SortKey { sort: BountySort::CreatedAt, value: 0, bounty_id: 0 }..)
+            .take_while(|(k, _)| k.sort == BountySort::CreatedAt)
+            .count() as u64
+    });
+    // Four entries per bounty, one per sort order
+    let status_indexed = STATUS_SORT_INDEX.with(|idx| idx.borrow().len()) / ALL_SORTS.len() as u64;
+    let total = BOUNTIES.with(|b| b.borrow().len());
+    if indexed == total && status_indexed == total {
+        return;
+    }
+    // Indexing from scratch; stale entries would be keyed by a status the bounty left
+    STATUS_SORT_INDEX.with(|idx| idx.borrow_mut().clear_new());
+    BOUNTIES.with(|bounties| {
+        for (_, bounty) in bounties.borrow().iter() {
+            reindex(None, &bounty);
+        }
+    });
+}
+
+/// Walk the index for `sort`, limited to bounties in `status` if given, starting
+/// strictly after `after` in the requested direction, until `visit` returns false.
+pub fn walk(
+    sort: BountySort,
+    status: Option<&BountyStatus>,
+    descending: bool,
+    after: Option<SortKey>,
+    mut visit: impl FnMut(&SortKey) -> bool,
+) {
+    let lowest = SortKey { sort, value: 0, bounty_id: 0 };
+    let highest = SortKey { sort, value: u128::MAX, bounty_id: u64::MAX };
+    match status {
+        None => SORT_INDEX.with(|idx| walk_range(&idx.borrow(), lowest, highest, after, descending, visit)),
+        Some(status) => STATUS_SORT_INDEX.with(|idx| {
+            let key = |k| status_sort_key(status, k);
+            walk_range(&idx.borrow(), key(lowest), key(highest), after.map(key), descending, |k| visit(&k.key))
+        }),
+    }
+}
+
+// Visit the keys of `idx` in `[lowest, highest]` strictly after `after`
+fn walk_range<K: Storable + Ord + Clone>(
+    idx: &StableBTreeMap<K, (), Memory>,
+    lowest: K,
+    highest: K,
+    after: Option<K>,
+    descending: bool,
+    mut visit: impl FnMut(&K) -> bool,
+) {
+    if descending {
+        let upper = match after {
+            Some(key) => Excluded(key),
+            None => Included(highest),
+        };
+        for (key, _) in idx.range((Included(lowest), upper)).rev() {
+            if !visit(&key) {
+                break;
+            }
+        }
+    } else {
+        let lower = match after {
+            Some(key) => Excluded(key),
+            None => Included(lowest),
+        };
+        for (key, _) in idx.range((lower, Included(highest))) {
+            if !visit(&key) {
+                break;
+            }
+        }
+    }
+}
+
+/// Opaque cursor naming the last bounty of a page.
+pub fn encode_cursor(key: &SortKey) -> String {
+    format!("{:02x}{:032x}{:016x}", key.sort as u8, key.value, key.bounty_id)
+}
+
+pub fn decode_cursor(cursor: &str, sort: BountySort) -> Result<SortKey, String> {
+    let invalid = || "Invalid cursor".to_string();
+    // from_str_radix would also take a leading '+'
+    if cursor.len() != 50 || !cursor.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let tag = u8::from_str_radix(&cursor[..2], 16).map_err(|_| invalid())?;
+    if tag != sort as u8 {
+        return Err("Cursor was issued for a different sort order".to_string());
+    }
+    let value = u128::from_str_radix(&cursor[2..34], 16).map_err(|_| invalid())?;
+    let bounty_id = u64::from_str_radix(&cursor[34..], 16).map_err(|_| invalid())?;
+    Ok(SortKey { sort, value, bounty_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save(previous: Option<&Bounty>, bounty: &Bounty) {
+        reindex(previous, bounty);
+        BOUNTIES.with(|b| b.borrow_mut().insert(bounty.id, bounty.clone()));
+    }
+
+    // Every id `walk` yields, reading `page` at a time and resuming from the encoded cursor
+    fn walk_pages(sort: BountySort, status: Option<&BountyStatus>, descending: bool, page: usize) -> Vec<u64> {
+        let mut ids = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let after = cursor.as_deref().map(|c| decode_cursor(c, sort).unwrap());
+            let mut keys = vec![];
+            walk(sort, status, descending, after, |key| {
+                keys.push(key.clone());
+                keys.len() < page
+            });
+            ids.extend(keys.iter().map(|k| k.bounty_id));
+            match keys.last() {
+                Some(last) if keys.len() == page => cursor = Some(encode_cursor(last)),
+                _ => return ids,
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        for key in [
+            SortKey { sort: BountySort::CreatedAt, value: 0, bounty_id: 0 },
+            SortKey { sort: BountySort::PrizeAmount, value: u128::MAX, bounty_id: u64::MAX },
+            SortKey { sort: BountySort::Submissions, value: 7, bounty_id: 42 },
+        ] {
+            let cursor = encode_cursor(&key);
+            assert_eq!(cursor.len(), 50);
+            assert!(decode_cursor(&cursor, key.sort) == Ok(key));
+        }
+    }
+
+    #[test]
+    fn tampered_cursors_are_rejected() {
+        let cursor = encode_cursor(&SortKey { sort: BountySort::Deadline, value: 5, bounty_id: 9 });
+        assert_eq!(decode_cursor(&cursor, BountySort::CreatedAt).err().unwrap(), "Cursor was issued for a different sort order");
+        for bad in [
+            String::new(),
+            cursor[1..].to_string(),
+            format!("{}0", cursor),
+            cursor.replace('9', "g"),
+            format!("{}é", &cursor[..48]),
+            format!("+{}", &cursor[1..]),
+        ] {
+            assert_eq!(decode_cursor(&bad, BountySort::Deadline).err().unwrap(), "Invalid cursor", "{}", bad);
+        }
+    }
+
+    #[test]
+    fn walks_continue_from_the_cursor() {
+        for id in 1..=7 {
+            save(None, &Bounty::for_test(id, BountyStatus::Active));
+        }
+        assert_eq!(walk_pages(BountySort::CreatedAt, None, false, 3), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(walk_pages(BountySort::CreatedAt, None, true, 2), vec![7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(walk_pages(BountySort::Deadline, None, false, 100), vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn status_walks_read_only_that_status() {
+        for id in 1..=6 {
+            save(None, &Bounty::for_test(id, BountyStatus::PendingFunding));
+        }
+        for id in [2, 5] {
+            let pending = Bounty::for_test(id, BountyStatus::PendingFunding);
+            save(Some(&pending), &Bounty { status: BountyStatus::Active, deadline: Some(100 - id), ..pending.clone() });
+        }
+        let active = Some(&BountyStatus::Active);
+        let pending = Some(&BountyStatus::PendingFunding);
+        assert_eq!(walk_pages(BountySort::CreatedAt, active, false, 1), vec![2, 5]);
+        assert_eq!(walk_pages(BountySort::Deadline, active, false, 1), vec![5, 2]);
+        assert_eq!(walk_pages(BountySort::PrizeAmount, pending, true, 2), vec![6, 4, 3, 1]);
+        assert_eq!(walk_pages(BountySort::Submissions, Some(&BountyStatus::Paid), false, 2), Vec::<u64>::new());
+
+        update_submission_count(5, &BountyStatus::Active, 0, 1);
+        assert_eq!(walk_pages(BountySort::Submissions, active, true, 5), vec![5, 2]);
+        assert_eq!(walk_pages(BountySort::Submissions, None, true, 1)[0], 5);
+    }
+}
@@ -0,0 +1,263 @@
+// Per-user notification inbox, so contributors and creators learn about the
+// bounties they are involved in without polling them.
+//
+// Notifications are derived from the activity log as entries are recorded,
+// plus an hourly timer that warns about approaching deadlines. The same timer
+// expires active bounties once their deadline passes, instead of waiting for
+// the next call that touches them, so "refund available" arrives on time.
+// Both scans read the index of Active bounties' deadlines starting from the
+// current time, in bounded batches, so bounties funded or edited after a scan
+// are still picked up; each deadline is warned about once.
+//
+// Each inbox keeps at most MAX_PER_RECIPIENT notifications and none older than
+// RETENTION_NS; the oldest are dropped as new ones arrive.
+use candid::Principal;
+use std::time::Duration;
+use crate::indices;
+use crate::lifecycle::{self, PayoutGuard};
+use crate::state::{BOUNTIES, NOTIFICATIONS};
+use crate::submissions;
+use crate::types::{BountyStatus, Event, EventKind, Notification, NotificationKind, NotificationPage, NotificationQuery};
+
+const MAX_PER_RECIPIENT: usize = 200;
+const NS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+const RETENTION_NS: u64 = 90 * NS_PER_DAY;
+// How far ahead of a deadline the creator and submitters are warned
+const DEADLINE_WARNING_NS: u64 = NS_PER_DAY;
+const TICK_INTERVAL: Duration = Duration::from_secs(3600);
+// Deadlines handled per scan per message; a full batch schedules another right away
+const SCAN_BATCH: u64 = 200;
+
+pub fn start_timer() {
+    ic_cdk_timers::set_timer_interval(TICK_INTERVAL, tick);
+}
+
+/// Notify whoever an activity log entry concerns.
+pub fn on_event(event: &Event) {
+    let Some(bounty_id) = event.bounty_id else {
+        return;
+    };
+    let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) else {
+        return;
+    };
+    let (recipient, kind) = match &event.kind {
+        EventKind::SubmissionAdded { submission_id, submitter } => (
+            bounty.creator,
+            NotificationKind::SubmissionReceived { submission_id: *submission_id, submitter: *submitter },
+        ),
+        EventKind::WinnerSelected { winner } => (*winner, NotificationKind::WinnerSelected),
+        // Sent even when the creator's own call noticed the expiry, since the
+        // refund still has to be claimed
+        EventKind::BountyExpired => {
+            push(bounty.creator, bounty_id, NotificationKind::RefundAvailable { amount: bounty.prize_amount }, event.timestamp);
+            return;
+        }
+        _ => return,
+    };
+    if recipient != event.actor {
+        push(recipient, bounty_id, kind, event.timestamp);
+    }
+}
+
+fn tick() {
+    let now = ic_cdk::api::time();
+    let more_expired = expire_overdue(now);
+    let more_warnings = warn_approaching(now);
+    if more_expired || more_warnings {
+        ic_cdk_timers::set_timer(Duration::ZERO, tick);
+    }
+}
+
+// Returns whether overdue bounties remain beyond this batch
+fn expire_overdue(now: u64) -> bool {
+    let ids = indices::overdue_active(now, SCAN_BATCH);
+    let mut expired = 0;
+    for bounty in indices::load(ids.iter().copied()) {
+        // Leave bounties with a payout in flight to the call that holds the guard
+        if let Ok(_guard) = PayoutGuard::acquire(bounty.id) {
+            if lifecycle::transition(bounty, BountyStatus::Expired, ic_cdk::api::id()).is_ok() {
+                expired += 1;
+            }
+        }
+    }
+    // Guarded bounties stay in the index; only go round again if this batch made progress
+    ids.len() as u64 == SCAN_BATCH && expired > 0
+}
+
+// Returns whether unwarned deadlines remain beyond this batch
+fn warn_approaching(now: u64) -> bool {
+    let entries = indices::unwarned_deadlines(now, now.saturating_add(DEADLINE_WARNING_NS), SCAN_BATCH);
+    for &(deadline, bounty_id) in &entries {
+        let mut recipients: Vec<Principal> = submissions::all(bounty_id).iter().map(|s| s.submitter).collect();
+        if let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) {
+            recipients.push(bounty.creator);
+        }
+        recipients.sort();
+        recipients.dedup();
+        for recipient in recipients {
+            push(recipient, bounty_id, NotificationKind::DeadlineApproaching { deadline }, now);
+        }
+        indices::mark_deadline_warned((deadline, bounty_id));
+    }
+    entries.len() as u64 == SCAN_BATCH
+}
+
+fn push(recipient: Principal, bounty_id: u64, kind: NotificationKind, created_at: u64) {
+    NOTIFICATIONS.with(|inbox| {
+        let mut inbox = inbox.borrow_mut();
+        let range = (recipient, 0)..=(recipient, u64::MAX);
+        // The newest notification is never pruned, so ids keep increasing
+        let id = inbox.range(range.clone()).next_back().map_or(0, |((_, id), _)| id + 1);
+        inbox.insert((recipient, id), Notification { id, bounty_id, kind, created_at, read: false });
+
+        let kept: Vec<(u64, u64)> = inbox.range(range).map(|((_, id), n)| (id, n.created_at)).collect();
+        let excess = kept.len().saturating_sub(MAX_PER_RECIPIENT);
+        let cutoff = created_at.saturating_sub(RETENTION_NS);
+        for (i, (old_id, old_created_at)) in kept.into_iter().enumerate() {
+            if i >= excess && old_created_at >= cutoff {
+                break;
+            }
+            inbox.remove(&(recipient, old_id));
+        }
+    });
+}
+
+/// Up to `limit` of `recipient`'s notifications, newest first.
+pub fn page(recipient: Principal, query: &NotificationQuery, limit: usize) -> NotificationPage {
+    let before = query.before.unwrap_or(u64::MAX);
+    let mut notifications: Vec<Notification> = NOTIFICATIONS.with(|inbox| {
+        inbox
+            .borrow()
+            .range((recipient, 0)..(recipient, before))
+            .rev()
+            .map(|(_, n)| n)
+            .filter(|n| !query.unread_only || !n.read)
+            .take(limit + 1)
+            .collect()
+    });
+    let next_before = if notifications.len() > limit {
+        notifications.truncate(limit);
+        notifications.last().map(|n| n.id)
+    } else {
+        None
+    };
+    NotificationPage { notifications, next_before, unread_count: unread_count(recipient) }
+}
+
+pub fn unread_count(recipient: Principal) -> u64 {
+    NOTIFICATIONS.with(|inbox| {
+        inbox.borrow().range((recipient, 0)..=(recipient, u64::MAX)).filter(|(_, n)| !n.read).count() as u64
+    })
+}
+
+/// Mark the given notifications read, or all of them when `ids` is None.
+/// Returns how many were unread.
+pub fn mark_read(recipient: Principal, ids: Option<Vec<u64>>) -> u64 {
+    NOTIFICATIONS.with(|inbox| {
+        let mut inbox = inbox.borrow_mut();
+        let unread: Vec<Notification> = match ids {
+            Some(ids) => ids.into_iter().filter_map(|id| inbox.get(&(recipient, id))).filter(|n| !n.read).collect(),
+            None => inbox.range((recipient, 0)..=(recipient, u64::MAX)).map(|(_, n)| n).filter(|n| !n.read).collect(),
+        };
+        let count = unread.len() as u64;
+        for mut notification in unread {
+            notification.read = true;
+            inbox.insert((recipient, notification.id), notification);
+        }
+        count
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Bounty;
+
+    fn bounty(id: u64, status: BountyStatus, deadline: Option<u64>) -> Bounty {
+        Bounty { deadline, ..Bounty::for_test(id, status) }
+    }
+
+    // Save a new version of a bounty, as save_bounty would
+    fn save(previous: Option<&Bounty>, bounty: &Bounty) {
+        indices::reindex(previous, bounty);
+        BOUNTIES.with(|b| b.borrow_mut().insert(bounty.id, bounty.clone()));
+    }
+
+    #[test]
+    fn overdue_scan_starts_from_now() {
+        let now = 10 * NS_PER_DAY;
+        let late = bounty(1, BountyStatus::PendingFunding, Some(now - NS_PER_DAY));
+        save(None, &late);
+        save(None, &bounty(2, BountyStatus::Active, Some(now + 1)));
+        save(None, &bounty(3, BountyStatus::Completed, Some(now - 1)));
+        assert!(indices::overdue_active(now, SCAN_BATCH).is_empty());
+
+        // Funded after its deadline, long after any earlier scan
+        let funded = Bounty { status: BountyStatus::Active, ..late.clone() };
+        save(Some(&late), &funded);
+        assert_eq!(indices::overdue_active(now, SCAN_BATCH), vec![1]);
+        assert_eq!(indices::overdue_active(now + 2, SCAN_BATCH), vec![1, 2]);
+        assert_eq!(indices::overdue_active(now + 2, 1), vec![1]);
+
+        let expired = Bounty { status: BountyStatus::Expired, ..funded.clone() };
+        save(Some(&funded), &expired);
+        assert_eq!(indices::overdue_active(now + 2, SCAN_BATCH), vec![2]);
+    }
+
+    #[test]
+    fn each_deadline_is_warned_once() {
+        let now = 10 * NS_PER_DAY;
+        let until = now + DEADLINE_WARNING_NS;
+        let soon = bounty(1, BountyStatus::Active, Some(now + 60));
+        save(None, &soon);
+        save(None, &bounty(2, BountyStatus::Active, Some(until + 1)));
+        assert_eq!(indices::unwarned_deadlines(now, until, SCAN_BATCH), vec![(now + 60, 1)]);
+
+        indices::mark_deadline_warned((now + 60, 1));
+        assert!(indices::unwarned_deadlines(now, until, SCAN_BATCH).is_empty());
+
+        // Edited into a window that was already scanned: warned again for the new deadline
+        let moved = Bounty { deadline: Some(now + 30), ..soon.clone() };
+        save(Some(&soon), &moved);
+        assert_eq!(indices::unwarned_deadlines(now, until, SCAN_BATCH), vec![(now + 30, 1)]);
+
+        // The other bounty comes into range as time passes
+        assert_eq!(indices::unwarned_deadlines(now + 2, until + 2, SCAN_BATCH), vec![(now + 30, 1), (until + 1, 2)]);
+        assert!(indices::unwarned_deadlines(until, until, SCAN_BATCH).is_empty());
+    }
+
+    fn inbox(recipient: Principal) -> Vec<(u64, u64)> {
+        NOTIFICATIONS.with(|n| {
+            n.borrow().range((recipient, 0)..=(recipient, u64::MAX)).map(|((_, id), n)| (id, n.created_at)).collect()
+        })
+    }
+
+    #[test]
+    fn inbox_keeps_the_newest_notifications() {
+        let recipient = Principal::from_slice(&[1]);
+        let other = Principal::from_slice(&[2]);
+        push(other, 0, NotificationKind::WinnerSelected, 1);
+        for i in 0..MAX_PER_RECIPIENT as u64 + 5 {
+            push(recipient, 0, NotificationKind::WinnerSelected, 1 + i);
+        }
+        let kept = inbox(recipient);
+        assert_eq!(kept.len(), MAX_PER_RECIPIENT);
+        assert_eq!(kept.first(), Some(&(5, 6)));
+        assert_eq!(kept.last(), Some(&(MAX_PER_RECIPIENT as u64 + 4, MAX_PER_RECIPIENT as u64 + 5)));
+        assert_eq!(inbox(other).len(), 1);
+    }
+
+    #[test]
+    fn inbox_drops_notifications_past_retention() {
+        let recipient = Principal::from_slice(&[1]);
+        push(recipient, 0, NotificationKind::WinnerSelected, NS_PER_DAY);
+        push(recipient, 0, NotificationKind::WinnerSelected, 2 * NS_PER_DAY);
+        push(recipient, 0, NotificationKind::WinnerSelected, NS_PER_DAY + RETENTION_NS + 1);
+        assert_eq!(inbox(recipient).iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1, 2]);
+
+        // Ids keep increasing after everything older has been pruned
+        push(recipient, 0, NotificationKind::WinnerSelected, 3 * RETENTION_NS);
+        assert_eq!(inbox(recipient), vec![(3, 3 * RETENTION_NS)]);
+        assert_eq!(page(recipient, &NotificationQuery { unread_only: true, before: None, limit: 10 }, 10).unread_count, 1);
+    }
+}
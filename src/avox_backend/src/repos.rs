@@ -0,0 +1,131 @@
+// Bounties grouped by the GitHub repository, and the owner, of the issue they
+// fund. ISSUE_INDEX is ordered by owner, repository and issue, so both groups
+// are contiguous ranges of it.
+use candid::{Nat, Principal};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use crate::indices;
+use crate::state::ISSUE_INDEX;
+use crate::types::{Bounty, BountyStatus, GithubIssueRef, IssueKey, OwnerStats, RepoContributor, RepoStats, TokenAmount};
+
+const HOST: &str = "github.com";
+
+/// Ids of the bounties on `owner`'s repositories, or on one of them, in
+/// repository and issue order.
+pub fn bounty_ids(owner: &str, repo: Option<&str>) -> Vec<u64> {
+    with_ids(owner, repo, |ids| ids.collect())
+}
+
+/// Up to `limit` ids of the bounties on a repository after skipping `offset`,
+/// in issue order, optionally only open ones. Reads no more of the index than the page needs.
+pub fn page_ids(owner: &str, repo: &str, open_only: bool, offset: u64, limit: u64) -> Vec<u64> {
+    with_ids(owner, Some(repo), |ids| {
+        ids.filter(|id| !open_only || is_open(*id))
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect()
+    })
+}
+
+// Hands `f` the ids in `owner`'s range of ISSUE_INDEX, read lazily
+fn with_ids<R>(owner: &str, repo: Option<&str>, f: impl FnOnce(&mut dyn Iterator<Item = u64>) -> R) -> R {
+    let start = IssueKey {
+        issue: GithubIssueRef {
+            host: HOST.to_string(),
+            owner: owner.to_string(),
+            repo: repo.unwrap_or_default().to_string(),
+            number: 0,
+        },
+        bounty_id: 0,
+    };
+    ISSUE_INDEX.with(|idx| {
+        let idx = idx.borrow();
+        let mut ids = idx
+            .range(start..)
+            .take_while(|(key, _)| {
+                key.issue.host == HOST && key.issue.owner == owner && repo.is_none_or(|r| key.issue.repo == r)
+            })
+            .map(|(key, _)| key.bounty_id);
+        f(&mut ids)
+    })
+}
+
+// Awaiting funding or active, read from the status index
+fn is_open(bounty_id: u64) -> bool {
+    indices::has_status(bounty_id, &BountyStatus::PendingFunding) || indices::has_status(bounty_id, &BountyStatus::Active)
+}
+
+pub fn repo_stats(owner: &str, repo: &str, top: usize) -> RepoStats {
+    stats(owner, Some(repo), &indices::load(bounty_ids(owner, Some(repo))), top)
+}
+
+pub fn owner_stats(owner: &str, top: usize) -> OwnerStats {
+    let bounties = indices::load(bounty_ids(owner, None));
+    let mut by_repo: BTreeMap<String, Vec<Bounty>> = BTreeMap::new();
+    for bounty in &bounties {
+        if let Ok(issue) = crate::github::parse_issue(&bounty.github_issue_url) {
+            by_repo.entry(issue.repo).or_default().push(bounty.clone());
+        }
+    }
+    let mut repos: Vec<RepoStats> = by_repo.iter().map(|(repo, bounties)| stats(owner, Some(repo), bounties, top)).collect();
+    repos.sort_by_key(|s| Reverse(s.bounty_count));
+    OwnerStats { total: stats(owner, None, &bounties, top), repos }
+}
+
+fn stats(owner: &str, repo: Option<&str>, bounties: &[Bounty], top: usize) -> RepoStats {
+    let (mut open_count, mut completed_count, mut unawarded_count) = (0, 0, 0);
+    let mut locked: BTreeMap<Principal, Nat> = BTreeMap::new();
+    let mut winners: BTreeMap<Principal, (u64, BTreeMap<Principal, Nat>)> = BTreeMap::new();
+    for bounty in bounties {
+        match bounty.status {
+            BountyStatus::PendingFunding => open_count += 1,
+            BountyStatus::Active => {
+                open_count += 1;
+                add(&mut locked, bounty);
+            }
+            BountyStatus::Completed | BountyStatus::Paid => {
+                completed_count += 1;
+                if bounty.status == BountyStatus::Completed {
+                    add(&mut locked, bounty);
+                }
+                if let Some(winner) = bounty.winner {
+                    let (won, amounts) = winners.entry(winner).or_default();
+                    *won += 1;
+                    add(amounts, bounty);
+                }
+            }
+            BountyStatus::Cancelled | BountyStatus::Expired | BountyStatus::Refunded => unawarded_count += 1,
+        }
+    }
+    let finished = completed_count + unawarded_count;
+    let completion_rate = (finished > 0).then(|| completed_count as f64 / finished as f64);
+    let mut top_contributors: Vec<RepoContributor> = winners
+        .into_iter()
+        .map(|(principal, (bounties_won, amounts))| RepoContributor {
+            principal,
+            bounties_won,
+            amount_won: amounts_of(amounts),
+        })
+        .collect();
+    top_contributors.sort_by_key(|c| Reverse(c.bounties_won));
+    top_contributors.truncate(top);
+    RepoStats {
+        owner: owner.to_string(),
+        repo: repo.map(str::to_string),
+        bounty_count: bounties.len() as u64,
+        open_count,
+        completed_count,
+        unawarded_count,
+        completion_rate,
+        value_locked: amounts_of(locked),
+        top_contributors,
+    }
+}
+
+fn add(totals: &mut BTreeMap<Principal, Nat>, bounty: &Bounty) {
+    *totals.entry(bounty.token_ledger).or_insert_with(|| Nat::from(0u64)) += bounty.prize_amount.clone();
+}
+
+fn amounts_of(totals: BTreeMap<Principal, Nat>) -> Vec<TokenAmount> {
+    totals.into_iter().map(|(token_ledger, amount)| TokenAmount { token_ledger, amount }).collect()
+}
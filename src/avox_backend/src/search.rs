@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, BTreeSet};
+use crate::state::{BOUNTIES, SEARCH_DOCS, SEARCH_INDEX};
+use crate::types::{Bounty, BountyStatus, SearchDoc, SearchPosting, TermKey};
+
+// Field weights: a hit in the title outranks one in the repo name, which outranks the description
+const TITLE_WEIGHT: u32 = 3;
+const REPO_WEIGHT: u32 = 2;
+const DESCRIPTION_WEIGHT: u32 = 1;
+
+const MIN_TERM_LENGTH: usize = 2;
+const MAX_TERM_LENGTH: usize = 32;
+const MAX_DESCRIPTION_TERMS: usize = 2000;
+// Upper bound on postings read per query term, to keep queries within the instruction limit
+const MAX_POSTINGS_PER_TERM: usize = 10_000;
+const MAX_QUERY_TERMS: usize = 8;
+
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on", "or",
+    "that", "the", "this", "to", "with",
+];
+
+/// Lowercased alphanumeric tokens with stop words and very short tokens removed.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|t| t.to_lowercase())
+        .filter(|t| t.chars().count() >= MIN_TERM_LENGTH && !STOP_WORDS.contains(&t.as_str()))
+        .map(|t| t.chars().take(MAX_TERM_LENGTH).collect())
+        .collect()
+}
+
+// "https://github.com/owner/repo/issues/1" -> "owner repo"
+fn repo_text(github_issue_url: &str) -> String {
+    let path = github_issue_url.split("://").last().unwrap_or_default();
+    path.split('/').skip(1).take(2).collect::<Vec<_>>().join(" ")
+}
+
+fn doc_terms(bounty: &Bounty) -> BTreeMap<String, u32> {
+    let mut terms: BTreeMap<String, u32> = BTreeMap::new();
+    let fields = [
+        (tokenize(&bounty.title), TITLE_WEIGHT),
+        (tokenize(&repo_text(&bounty.github_issue_url)), REPO_WEIGHT),
+        (tokenize(&bounty.description).into_iter().take(MAX_DESCRIPTION_TERMS).collect(), DESCRIPTION_WEIGHT),
+    ];
+    for (tokens, weight) in fields {
+        for token in tokens {
+            *terms.entry(token).or_insert(0) += weight;
+        }
+    }
+    terms
+}
+
+/// (Re)index a bounty after it is created, edited or changes status.
+/// Postings carry the status so status filters never have to load the bounty.
+pub fn index_bounty(bounty: &Bounty) {
+    let terms = doc_terms(bounty);
+    let previous = SEARCH_DOCS.with(|docs| docs.borrow().get(&bounty.id));
+    if let Some(previous) = &previous {
+        let unchanged = previous.status == bounty.status
+            && previous.terms.len() == terms.len()
+            && previous.terms.iter().all(|(t, w)| terms.get(t) == Some(w));
+        if unchanged {
+            return;
+        }
+    }
+    SEARCH_INDEX.with(|idx| {
+        let mut idx = idx.borrow_mut();
+        if let Some(previous) = &previous {
+            for (term, _) in &previous.terms {
+                idx.remove(&TermKey { term: term.clone(), bounty_id: bounty.id });
+            }
+        }
+        for (term, weight) in &terms {
+            idx.insert(
+                TermKey { term: term.clone(), bounty_id: bounty.id },
+                SearchPosting { weight: *weight, status: bounty.status.clone() },
+            );
+        }
+    });
+    SEARCH_DOCS.with(|docs| {
+        docs.borrow_mut().insert(bounty.id, SearchDoc {
+            terms: terms.into_iter().collect(),
+            status: bounty.status.clone(),
+        });
+    });
+}
+
+/// Index bounties created before search existed. Safe to call repeatedly.
+pub fn backfill() {
+    let missing: Vec<Bounty> = BOUNTIES.with(|bounties| {
+        let bounties = bounties.borrow();
+        SEARCH_DOCS.with(|docs| {
+            let docs = docs.borrow();
+            bounties.iter().filter(|(id, _)| !docs.contains_key(id)).map(|(_, b)| b).collect()
+        })
+    });
+    for bounty in &missing {
+        index_bounty(bounty);
+    }
+}
+
+/// Score every bounty matching all query terms. Each query term also matches as a prefix,
+/// so partially typed words still find results. Scores are weighted by inverse document
+/// frequency so rare words count for more than common ones.
+pub fn rank(query: &str, status: Option<&BountyStatus>, allowed: Option<&BTreeSet<u64>>) -> Vec<(f64, u64)> {
+    let query_terms: Vec<String> = tokenize(query).into_iter().take(MAX_QUERY_TERMS).collect();
+    if query_terms.is_empty() {
+        return vec![];
+    }
+    let total_docs = SEARCH_DOCS.with(|docs| docs.borrow().len()).max(1) as f64;
+    let mut scores: Option<BTreeMap<u64, f64>> = None;
+    for term in &query_terms {
+        let mut term_scores: BTreeMap<u64, f64> = BTreeMap::new();
+        let start = TermKey { term: term.clone(), bounty_id: 0 };
+        SEARCH_INDEX.with(|idx| {
+            for (key, posting) in idx.borrow()
+                .range(start..)
+                .take_while(|(key, _)| key.term.starts_with(term.as_str()))
+                .take(MAX_POSTINGS_PER_TERM)
+            {
+                if status.is_some_and(|s| &posting.status != s) {
+                    continue;
+                }
+                if allowed.is_some_and(|ids| !ids.contains(&key.bounty_id)) {
+                    continue;
+                }
+                // Exact matches beat prefix matches
+                let exact = if key.term == *term { 1.0 } else { 0.5 };
+                *term_scores.entry(key.bounty_id).or_insert(0.0) += posting.weight as f64 * exact;
+            }
+        });
+        let idf = (1.0 + total_docs / (term_scores.len().max(1) as f64)).ln();
+        scores = Some(match scores {
+            None => term_scores.into_iter().map(|(id, s)| (id, s * idf)).collect(),
+            Some(acc) => acc.into_iter()
+                .filter_map(|(id, s)| term_scores.get(&id).map(|t| (id, s + t * idf)))
+                .collect(),
+        });
+    }
+    let mut ranked: Vec<(f64, u64)> = scores.unwrap_or_default().into_iter().map(|(id, s)| (s, id)).collect();
+    // Highest score first, ties broken by lowest id so the order is stable across pages
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    ranked
+}
+
+/// Index of the first hit after `after` in a list ordered by (score desc, id asc).
+pub fn resume_position(ranked: &[(f64, u64)], after: Option<(f64, u64)>) -> usize {
+    match after {
+        Some((score, id)) => ranked.partition_point(|(s, i)| *s > score || (*s == score && *i <= id)),
+        None => 0,
+    }
+}
+
+/// Opaque cursor naming the last hit of a page.
+pub fn encode_cursor(score: f64, bounty_id: u64) -> String {
+    format!("{:016x}{:016x}", score.to_bits(), bounty_id)
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<(f64, u64), String> {
+    let invalid = || "Invalid cursor".to_string();
+    if cursor.len() != 32 || !cursor.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let bits = u64::from_str_radix(&cursor[..16], 16).map_err(|_| invalid())?;
+    let bounty_id = u64::from_str_radix(&cursor[16..], 16).map_err(|_| invalid())?;
+    Ok((f64::from_bits(bits), bounty_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(id: u64, title: &str) {
+        let mut bounty = Bounty::for_test(id, BountyStatus::Active);
+        bounty.title = title.to_string();
+        index_bounty(&bounty);
+    }
+
+    #[test]
+    fn tokens_are_lowercased_words_without_stop_words() {
+        assert_eq!(tokenize("Fix the Parser, and add I/O tests!"), ["fix", "parser", "add", "tests"]);
+        assert_eq!(tokenize("a b c x"), Vec::<String>::new());
+        assert_eq!(tokenize("Ünïcode-Straße"), ["ünïcode", "straße"]);
+        assert_eq!(tokenize(&"x".repeat(40)), ["x".repeat(MAX_TERM_LENGTH)]);
+    }
+
+    #[test]
+    fn query_terms_match_as_prefixes() {
+        index(1, "Parser rewrite");
+        index(2, "Parse errors");
+        index(3, "Sparse matrix");
+        let ids = |query: &str| {
+            let mut ids: Vec<u64> = rank(query, None, None).into_iter().map(|(_, id)| id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("pars"), [1, 2]);
+        assert_eq!(ids("parser"), [1]);
+        assert_eq!(ids("parse errors"), [2]);
+        assert!(ids("the").is_empty());
+        // An exact hit outranks a prefix hit
+        assert_eq!(rank("parse", None, None)[0].1, 2);
+    }
+
+    #[test]
+    fn cursors_continue_after_the_last_hit() {
+        let ranked = [(3.0, 4), (2.0, 1), (2.0, 5), (2.0, 9), (1.0, 2)];
+        assert_eq!(resume_position(&ranked, None), 0);
+        for (i, &(score, id)) in ranked.iter().enumerate() {
+            let after = decode_cursor(&encode_cursor(score, id)).unwrap();
+            assert_eq!(after, (score, id));
+            assert_eq!(resume_position(&ranked, Some(after)), i + 1);
+        }
+        // A hit that dropped out since the cursor was issued still resumes in order
+        assert_eq!(resume_position(&ranked, Some((2.0, 6))), 3);
+        assert_eq!(resume_position(&ranked, Some((2.5, 0))), 1);
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        let cursor = encode_cursor(1.5, 7);
+        assert!(decode_cursor(&cursor[1..]).is_err());
+        assert!(decode_cursor(&format!("+{}", &cursor[1..])).is_err());
+        assert!(decode_cursor(&cursor.replace('0', "g")).is_err());
+        assert!(decode_cursor("").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use candid::Nat;
+use std::time::Duration;
+use crate::escrow;
+use crate::state::{BOUNTIES, SERIES, SERIES_BOUNTIES};
+use crate::types::{BountyIdList, BountyStatus, CreateBountyRequest, RecurringSeries, SeriesStatus};
+
+// How often the canister looks for series that are due
+const SERIES_TICK_INTERVAL: Duration = Duration::from_secs(600);
+
+// Timers do not survive upgrades, so this runs from both init and post_upgrade
+pub fn start_timer() {
+    ic_cdk_timers::set_timer_interval(SERIES_TICK_INTERVAL, || ic_cdk::spawn(run_due_series()));
+}
+
+pub fn get_series(series_id: u64) -> Option<RecurringSeries> {
+    SERIES.with(|series| series.borrow().get(&series_id))
+}
+
+pub fn save_series(series: RecurringSeries) {
+    SERIES.with(|s| s.borrow_mut().insert(series.id, series));
+}
+
+// Build the request a series would pass to `create_bounty` if it ran at `now`
+pub fn spawn_request(series: &RecurringSeries, now: u64) -> CreateBountyRequest {
+    let template = &series.template;
+    CreateBountyRequest {
+        title: template.title.clone(),
+        description: template.description.clone(),
+        github_issue_url: template.github_issue_url.clone(),
+        prize_amount: template.prize_amount.clone(),
+        token_ledger: template.token_ledger,
+        from_subaccount: None,
+        fee: None,
+        deadline: template.deadline_offset_ns.map(|offset| now.saturating_add(offset)),
+        competition_mode: template.competition_mode.clone(),
+        max_reservation_ns: template.max_reservation_ns,
+        org_id: series.org_id,
+        labels: template.labels.clone(),
+        pr_requirement: template.pr_requirement.clone(),
+        // Each period's bounty funds the same issue by design
+        stack: Some(true),
+    }
+}
+
+async fn run_due_series() {
+    let now = ic_cdk::api::time();
+    let due: Vec<u64> = SERIES.with(|series| {
+        series.borrow()
+            .iter()
+            .filter(|(_, s)| s.status == SeriesStatus::Active && s.next_run_at <= now)
+            .map(|(id, _)| id)
+            .collect()
+    });
+    for series_id in due {
+        if let Err(e) = spawn_next(series_id).await {
+            // Stop spending until the creator has looked at the problem. A series
+            // cancelled or paused while the spawn was awaiting stays as it is.
+            if let Some(mut series) = get_series(series_id).filter(|s| s.status == SeriesStatus::Active) {
+                series.status = SeriesStatus::Paused;
+                series.last_error = Some(e);
+                save_series(series);
+            }
+        }
+    }
+}
+
+// Re-read a series after an await; it may have been paused or cancelled meanwhile
+fn active_series(series_id: u64) -> Result<RecurringSeries, String> {
+    get_series(series_id)
+        .filter(|s| s.status == SeriesStatus::Active)
+        .ok_or_else(|| "Series is no longer active".to_string())
+}
+
+async fn spawn_next(series_id: u64) -> Result<u64, String> {
+    let now = ic_cdk::api::time();
+    let mut series = get_series(series_id).ok_or("Series not found")?;
+    // Claim this run before awaiting so overlapping ticks cannot spawn twice.
+    // Missed runs are skipped rather than spawned in a burst.
+    series.next_run_at = series.next_run_at.saturating_add(series.interval_ns).max(now.saturating_add(1));
+    save_series(series.clone());
+
+    let ledger = series.template.token_ledger;
+    let prize = series.template.prize_amount.clone();
+    let fee = escrow::get_fee(ledger).await?;
+    let balance = escrow::check_balance(ledger, series.budget_subaccount).await?;
+    // Escrow holds prize + fee so the payout's own fee is covered, and moving
+    // that into escrow costs one more fee from the budget
+    let deposit = prize + fee.clone();
+    let needed = deposit.clone() + fee;
+    if balance < needed {
+        return Err(format!("Insufficient budget: {} available, {} needed", balance, needed));
+    }
+
+    escrow::token_decimals(ledger).await?;
+    let series = active_series(series_id)?;
+    let request = spawn_request(&series, now);
+    crate::validate_bounty_request(series.creator, &request)?;
+    let (bounty_id, subaccount) = crate::store_new_bounty(series.creator, request, Some(series_id));
+    SERIES_BOUNTIES.with(|series_bounties| {
+        let mut sb = series_bounties.borrow_mut();
+        let mut bounty_list = sb.get(&series_id).unwrap_or(BountyIdList(vec![]));
+        bounty_list.0.push(bounty_id);
+        sb.insert(series_id, bounty_list);
+    });
+    let to = escrow::get_escrow_account(subaccount);
+    let transfer = escrow::transfer_to_account(ledger, series.budget_subaccount, to, deposit).await;
+    let Some(bounty) = BOUNTIES.with(|bounties| bounties.borrow().get(&bounty_id)) else {
+        return Err("Spawned bounty disappeared".to_string());
+    };
+    if let Err(e) = transfer {
+        // Nothing reached the escrow; drop the bounty rather than leave it awaiting funding
+        crate::lifecycle::transition(bounty, BountyStatus::Cancelled, ic_cdk::api::id())?;
+        return Err(e);
+    }
+
+    // The escrow now holds the prize plus the payout fee, so the bounty is funded
+    crate::lifecycle::transition(bounty, BountyStatus::Active, ic_cdk::api::id())?;
+    if let Some(mut series) = get_series(series_id) {
+        series.spawned_count += 1;
+        series.last_error = None;
+        save_series(series);
+    }
+    Ok(bounty_id)
+}
+
+// Return whatever is left in the budget, less the transfer fee, to `to`
+pub async fn refund_budget(series: &RecurringSeries, to: candid::Principal) -> Result<Nat, String> {
+    escrow::sweep(series.template.token_ledger, series.budget_subaccount, to).await
+}
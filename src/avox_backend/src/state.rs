@@ -1,11 +1,17 @@
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
-use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell, StableLog};
 use std::cell::RefCell;
-use crate::types::{Bounty, BountyIdList, ClaimKey, UserProfile};
+use crate::types::{
+    Application, Bounty, BountyIdList, ClaimKey, Event, GithubChallenge, IssueKey, LabelKey, Maintainer, Notification, OrgMember, Organization, RecurringSeries, Reservation,
+    SearchDoc, SearchPosting, SortKey, StatusSortKey, StatusTransition, Submission, TermKey, UserProfile,
+};
 use candid::Principal;
 
 pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 
+// Layout version written by init; post_upgrade migrates anything older
+pub const CURRENT_SCHEMA_VERSION: u64 = 1;
+
 thread_local! {
     pub static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = 
         RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -32,12 +38,8 @@ thread_local! {
         )
     );
 
-    // Memory ID 3: User created bounties mapping
-    pub static USER_CREATED_BOUNTIES: RefCell<StableBTreeMap<Principal, BountyIdList, Memory>> = RefCell::new(
-        StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(3))),
-        )
-    );
+    // Memory ID 3: Reserved. Held a creator -> bounties mapping that was never populated;
+    // superseded by CREATOR_INDEX (Memory ID 26). Do not reuse.
 
     // Memory ID 4: Claims tracking
     pub static CLAIMS: RefCell<StableBTreeMap<ClaimKey, bool, Memory>> = RefCell::new(
@@ -52,4 +54,293 @@ thread_local! {
             MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(5))),
         )
     );
-} 
\ No newline at end of file
+
+    // Memory ID 6: Work reservations (one per bounty)
+    pub static RESERVATIONS: RefCell<StableBTreeMap<u64, Reservation, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(6))),
+        )
+    );
+
+    // Memory ID 7: Applications keyed by (bounty_id, applicant)
+    pub static APPLICATIONS: RefCell<StableBTreeMap<(u64, Principal), Application, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(7))),
+        )
+    );
+
+    // Memory ID 8: Bounties each user has applied to
+    pub static USER_APPLICATIONS: RefCell<StableBTreeMap<Principal, BountyIdList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(8))),
+        )
+    );
+
+    // Memory ID 9: Co-maintainers keyed by (bounty_id, principal)
+    pub static MAINTAINERS: RefCell<StableBTreeMap<(u64, Principal), Maintainer, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(9))),
+        )
+    );
+
+    // Memory ID 10: Organizations
+    pub static ORGANIZATIONS: RefCell<StableBTreeMap<u64, Organization, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(10))),
+        )
+    );
+
+    // Memory ID 11: Organization counter
+    pub static ORG_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(11))),
+            0
+        ).expect("Failed to initialize organization counter")
+    );
+
+    // Memory ID 12: Unique organization handle -> org id
+    pub static ORG_HANDLES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(12))),
+        )
+    );
+
+    // Memory ID 13: Organization members keyed by (org_id, principal)
+    pub static ORG_MEMBERS: RefCell<StableBTreeMap<(u64, Principal), OrgMember, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(13))),
+        )
+    );
+
+    // Memory ID 14: Reverse membership index (principal, org_id)
+    pub static USER_ORGS: RefCell<StableBTreeMap<(Principal, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(14))),
+        )
+    );
+
+    // Memory ID 15: Bounties posted on behalf of each organization
+    pub static ORG_BOUNTIES: RefCell<StableBTreeMap<u64, BountyIdList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(15))),
+        )
+    );
+
+    // Memory ID 16: Recurring bounty series
+    pub static SERIES: RefCell<StableBTreeMap<u64, RecurringSeries, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(16))),
+        )
+    );
+
+    // Memory ID 17: Series counter
+    pub static SERIES_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(17))),
+            0
+        ).expect("Failed to initialize series counter")
+    );
+
+    // Memory ID 18: Bounties spawned by each series
+    pub static SERIES_BOUNTIES: RefCell<StableBTreeMap<u64, BountyIdList, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(18))),
+        )
+    );
+
+    // Memory ID 19: Series created by each user (principal, series_id)
+    pub static USER_SERIES: RefCell<StableBTreeMap<(Principal, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(19))),
+        )
+    );
+
+    // Memory ID 20: Label index (kind, value, bounty_id)
+    pub static LABEL_INDEX: RefCell<StableBTreeMap<LabelKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(20))),
+        )
+    );
+
+    // Memory ID 21: Full-text inverted index (term, bounty_id) -> posting
+    pub static SEARCH_INDEX: RefCell<StableBTreeMap<TermKey, SearchPosting, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(21))),
+        )
+    );
+
+    // Memory ID 22: Terms currently indexed for each bounty
+    pub static SEARCH_DOCS: RefCell<StableBTreeMap<u64, SearchDoc, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(22))),
+        )
+    );
+
+    // Memory ID 23: Sort index (sort order, value, bounty_id)
+    pub static SORT_INDEX: RefCell<StableBTreeMap<SortKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(23))),
+        )
+    );
+
+    // Memory ID 24: Cached icrc1_decimals per token ledger
+    pub static LEDGER_DECIMALS: RefCell<StableBTreeMap<Principal, u8, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(24))),
+        )
+    );
+
+    // Memory ID 25: Status index (status code, bounty_id)
+    pub static STATUS_INDEX: RefCell<StableBTreeMap<(u8, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(25))),
+        )
+    );
+
+    // Memory ID 26: Creator index (creator, bounty_id)
+    pub static CREATOR_INDEX: RefCell<StableBTreeMap<(Principal, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(26))),
+        )
+    );
+
+    // Memory ID 27: Token ledger index (ledger, bounty_id)
+    pub static LEDGER_INDEX: RefCell<StableBTreeMap<(Principal, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(27))),
+        )
+    );
+
+    // Memory ID 28: Deadline index (deadline, bounty_id); bounties without a deadline are absent
+    pub static DEADLINE_INDEX: RefCell<StableBTreeMap<(u64, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(28))),
+        )
+    );
+
+    // Memory ID 29: Submissions keyed by (bounty_id, submission_id)
+    pub static SUBMISSIONS: RefCell<StableBTreeMap<(u64, u64), Submission, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(29))),
+        )
+    );
+
+    // Memory ID 30: (bounty_id, submitter) -> submission_id
+    pub static SUBMITTER_INDEX: RefCell<StableBTreeMap<(u64, Principal), u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(30))),
+        )
+    );
+
+    // Memory ID 31: Version of the stored data layout, bumped by post_upgrade migrations
+    pub static SCHEMA_VERSION: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(31))),
+            0
+        ).expect("Failed to initialize schema version")
+    );
+
+    // Memory ID 32: Bounty status history keyed by (bounty_id, seq)
+    pub static STATUS_TRANSITIONS: RefCell<StableBTreeMap<(u64, u64), StatusTransition, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(32))),
+        )
+    );
+
+    // Memory IDs 33 and 34: Append-only activity log (index and data)
+    pub static EVENTS: RefCell<StableLog<Event, Memory, Memory>> = RefCell::new(
+        StableLog::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(33))),
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(34))),
+        ).expect("Failed to initialize event log")
+    );
+
+    // Memory ID 35: Events touching a bounty (bounty_id, event index)
+    pub static BOUNTY_EVENTS: RefCell<StableBTreeMap<(u64, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(35))),
+        )
+    );
+
+    // Memory ID 36: Events a principal performed or is named in (principal, event index)
+    pub static PRINCIPAL_EVENTS: RefCell<StableBTreeMap<(Principal, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(36))),
+        )
+    );
+
+    // Memory ID 37: Cached icrc1_symbol per token ledger
+    pub static LEDGER_SYMBOLS: RefCell<StableBTreeMap<Principal, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(37))),
+        )
+    );
+
+    // Memory ID 38: Pending GitHub ownership challenges, one per principal
+    pub static GITHUB_CHALLENGES: RefCell<StableBTreeMap<Principal, GithubChallenge, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(38))),
+        )
+    );
+
+    // Memory ID 39: Verified GitHub login (lowercase) -> principal
+    pub static GITHUB_LOGINS: RefCell<StableBTreeMap<String, Principal, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(39))),
+        )
+    );
+
+    // Memory ID 40: GitHub webhook secret per repository ("owner/repo", lowercase)
+    pub static REPO_WEBHOOK_SECRETS: RefCell<StableBTreeMap<String, String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(40))),
+        )
+    );
+
+    // Memory ID 41: bounties by the GitHub issue they fund
+    pub static ISSUE_INDEX: RefCell<StableBTreeMap<IssueKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(41))),
+        )
+    );
+
+    // Memory ID 42: per-user notification inbox, keyed by (recipient, notification id)
+    pub static NOTIFICATIONS: RefCell<StableBTreeMap<(Principal, u64), Notification, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(42))),
+        )
+    );
+
+    // Memory IDs 43 and 44 held the notification scan cursors, replaced by
+    // ACTIVE_DEADLINE_INDEX. Retired; do not reuse them.
+
+    // Memory ID 45: X-GitHub-Delivery ids already acted on, with when they arrived
+    pub static WEBHOOK_DELIVERIES: RefCell<StableBTreeMap<String, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(45))),
+        )
+    );
+
+    // Memory ID 46: the same deliveries ordered by arrival, for pruning.
+    // Keyed by (arrival time, sequence within that time)
+    pub static WEBHOOK_DELIVERY_LOG: RefCell<StableBTreeMap<(u64, u64), String, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(46))),
+        )
+    );
+
+    // Memory ID 47: (deadline, bounty id) of Active bounties only, with whether
+    // the "deadline approaching" notification went out for that deadline
+    pub static ACTIVE_DEADLINE_INDEX: RefCell<StableBTreeMap<(u64, u64), bool, Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(47))),
+        )
+    );
+
+    // Memory ID 48: Sort index grouped by status (status code, sort order, value, bounty_id)
+    pub static STATUS_SORT_INDEX: RefCell<StableBTreeMap<StatusSortKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(48))),
+        )
+    );
+}
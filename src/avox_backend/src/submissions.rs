@@ -0,0 +1,130 @@
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::StableBTreeMap;
+use crate::state::{Memory, BOUNTIES, MEMORY_MANAGER, SCHEMA_VERSION, SUBMISSIONS, SUBMITTER_INDEX};
+use crate::types::{EmbeddedSubmissions, PrVerification, Submission};
+
+// Schema version in which submissions moved out of the bounty record
+const SEPARATE_SUBMISSIONS_VERSION: u64 = 1;
+
+/// Number of submissions on a bounty. Ids are dense, so this is the last id plus one.
+pub fn count(bounty_id: u64) -> u64 {
+    SUBMISSIONS.with(|subs| {
+        subs.borrow()
+            .range((bounty_id, 0)..=(bounty_id, u64::MAX))
+            .next_back()
+            .map(|((_, id), _)| id + 1)
+            .unwrap_or(0)
+    })
+}
+
+pub fn get(bounty_id: u64, submission_id: u64) -> Option<Submission> {
+    SUBMISSIONS.with(|subs| subs.borrow().get(&(bounty_id, submission_id)))
+}
+
+pub fn has_submitted(bounty_id: u64, submitter: Principal) -> bool {
+    SUBMITTER_INDEX.with(|idx| idx.borrow().contains_key(&(bounty_id, submitter)))
+}
+
+/// Store a new submission under the next id for its bounty.
+pub fn add(bounty_id: u64, submitter: Principal, pr_url: String, comment: String, submitted_at: u64) -> Submission {
+    let submission = Submission {
+        id: count(bounty_id),
+        bounty_id,
+        submitter,
+        pr_url,
+        comment,
+        submitted_at,
+        verification: None,
+    };
+    SUBMISSIONS.with(|subs| subs.borrow_mut().insert((bounty_id, submission.id), submission.clone()));
+    SUBMITTER_INDEX.with(|idx| idx.borrow_mut().insert((bounty_id, submitter), submission.id));
+    submission
+}
+
+/// Id of the submission a principal made on a bounty.
+pub fn id_of(bounty_id: u64, submitter: Principal) -> Option<u64> {
+    SUBMITTER_INDEX.with(|idx| idx.borrow().get(&(bounty_id, submitter)))
+}
+
+/// Attach the result of a GitHub check to an existing submission.
+pub fn set_verification(bounty_id: u64, submission_id: u64, verification: PrVerification) {
+    SUBMISSIONS.with(|subs| {
+        let mut subs = subs.borrow_mut();
+        if let Some(mut submission) = subs.get(&(bounty_id, submission_id)) {
+            submission.verification = Some(verification);
+            subs.insert((bounty_id, submission_id), submission);
+        }
+    });
+}
+
+/// Every submission on a bounty, oldest first.
+pub fn all(bounty_id: u64) -> Vec<Submission> {
+    SUBMISSIONS.with(|subs| subs.borrow().range((bounty_id, 0)..=(bounty_id, u64::MAX)).map(|(_, s)| s).collect())
+}
+
+/// Up to `limit` submissions with id greater than `start_after`, oldest first.
+pub fn page(bounty_id: u64, start_after: Option<u64>, limit: usize) -> (Vec<Submission>, Option<u64>) {
+    let from = match start_after {
+        Some(id) if id == u64::MAX => return (vec![], None),
+        Some(id) => id + 1,
+        None => 0,
+    };
+    let mut page: Vec<Submission> = SUBMISSIONS.with(|subs| {
+        subs.borrow()
+            .range((bounty_id, from)..=(bounty_id, u64::MAX))
+            .take(limit + 1)
+            .map(|(_, s)| s)
+            .collect()
+    });
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|s| s.id)
+    } else {
+        None
+    };
+    (page, next_cursor)
+}
+
+/// Move submissions embedded in pre-existing bounty records into SUBMISSIONS and
+/// rewrite those bounties without them. Runs once, from post_upgrade.
+pub fn migrate_embedded() {
+    if SCHEMA_VERSION.with(|v| *v.borrow().get()) >= SEPARATE_SUBMISSIONS_VERSION {
+        return;
+    }
+    // Read-only view of the bounty map that decodes just the legacy submissions field
+    let embedded: Vec<(u64, Vec<Submission>)> = {
+        let legacy: StableBTreeMap<u64, EmbeddedSubmissions, Memory> =
+            StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(MemoryId::new(0))));
+        legacy.iter()
+            .filter_map(|(bounty_id, e)| e.submissions.map(|subs| (bounty_id, subs)))
+            .filter(|(_, subs)| !subs.is_empty())
+            .map(|(bounty_id, subs)| {
+                let subs = subs.into_iter().enumerate().map(|(i, s)| Submission {
+                    id: i as u64,
+                    bounty_id,
+                    submitter: s.submitter,
+                    pr_url: s.pr_url,
+                    comment: s.comment,
+                    submitted_at: s.submitted_at,
+                    verification: None,
+                }).collect();
+                (bounty_id, subs)
+            })
+            .collect()
+    };
+    for (bounty_id, subs) in embedded {
+        for submission in subs {
+            SUBMITTER_INDEX.with(|idx| idx.borrow_mut().insert((bounty_id, submission.submitter), submission.id));
+            SUBMISSIONS.with(|s| s.borrow_mut().insert((bounty_id, submission.id), submission));
+        }
+        // Re-encoding through the current type drops the embedded copy
+        BOUNTIES.with(|bounties| {
+            let mut bounties = bounties.borrow_mut();
+            if let Some(bounty) = bounties.get(&bounty_id) {
+                bounties.insert(bounty_id, bounty);
+            }
+        });
+    }
+    SCHEMA_VERSION.with(|v| v.borrow_mut().set(SEPARATE_SUBMISSIONS_VERSION).expect("Failed to update schema version"));
+}
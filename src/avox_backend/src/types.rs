@@ -13,11 +13,64 @@ pub struct Bounty {
     pub prize_amount: Nat,
     pub token_ledger: Principal,
     pub status: BountyStatus,
-    pub submissions: Vec<Submission>,
     pub winner: Option<Principal>,
     pub created_at: u64,
     pub escrow_subaccount: [u8; 32],
     pub deadline: Option<u64>,
+    pub competition_mode: Option<CompetitionMode>, // None for legacy bounties (Open)
+    pub max_reservation_ns: Option<u64>,           // None disables reservations
+    pub org_id: Option<u64>,                       // organization the bounty is posted on behalf of
+    pub series_id: Option<u64>,                    // recurring series that spawned the bounty
+    pub labels: Option<BountyLabels>,
+    pub pr_requirement: Option<PrRequirement>,     // None trusts the creator's choice of winner
+    pub issue_closed_at: Option<u64>,              // set by the GitHub webhook, cleared on reopen
+    pub funded_at: Option<u64>,                    // when it last became Active; None if never, or before this was tracked
+}
+
+impl Bounty {
+    pub fn competition_mode(&self) -> CompetitionMode {
+        self.competition_mode.clone().unwrap_or(CompetitionMode::Open)
+    }
+
+    // A plain 100-token bounty on github.com/owner/repo/issues/<id>, for unit tests
+    #[cfg(test)]
+    pub fn for_test(id: u64, status: BountyStatus) -> Self {
+        Bounty {
+            id,
+            creator: Principal::anonymous(),
+            title: format!("Bounty {}", id),
+            description: String::new(),
+            github_issue_url: format!("https://github.com/owner/repo/issues/{}", id),
+            prize_amount: Nat::from(100u64),
+            token_ledger: Principal::anonymous(),
+            status,
+            winner: None,
+            created_at: id,
+            escrow_subaccount: [0u8; 32],
+            deadline: None,
+            competition_mode: None,
+            max_reservation_ns: None,
+            org_id: None,
+            series_id: None,
+            labels: None,
+            pr_requirement: None,
+            issue_closed_at: None,
+            funded_at: None,
+        }
+    }
+}
+
+// Compact listing view of a bounty; get_bounty returns the full record
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BountySummary {
+    pub id: u64,
+    pub title: String,
+    pub status: BountyStatus,
+    pub prize_amount: Nat,
+    pub token_ledger: Principal,
+    pub deadline: Option<u64>,
+    pub submission_count: u64,
+    pub creator: Principal,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
@@ -27,16 +80,576 @@ pub enum BountyStatus {
     Completed,
     Cancelled,
     Expired,
+    Paid,     // Completed and the winner has received the prize
+    Refunded, // Expired and the prize has been returned to the creator
+}
+
+impl BountyStatus {
+    // Stable one-byte key used by the status index. Never renumber existing variants.
+    pub fn code(&self) -> u8 {
+        match self {
+            BountyStatus::PendingFunding => 0,
+            BountyStatus::Active => 1,
+            BountyStatus::Completed => 2,
+            BountyStatus::Cancelled => 3,
+            BountyStatus::Expired => 4,
+            BountyStatus::Paid => 5,
+            BountyStatus::Refunded => 6,
+        }
+    }
+}
+
+// Structured discovery labels. Values are normalized to lowercase on write.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct BountyLabels {
+    pub tags: Vec<String>,      // e.g. "good-first-issue", "documentation"
+    pub languages: Vec<String>, // e.g. "rust", "typescript"
+    pub skills: Vec<String>,    // e.g. "wasm", "frontend", "security"
+    pub estimated_hours: Option<u32>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LabelKind {
+    Tag,
+    Language,
+    Skill,
+}
+
+// Secondary index entry: one per (label, bounty)
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LabelKey {
+    pub kind: LabelKind,
+    pub value: String,
+    pub bounty_id: u64,
+}
+
+// All criteria are combined with AND; empty lists and None match everything
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct BountyFilter {
+    pub status: Option<BountyStatus>,
+    pub tags: Vec<String>,
+    pub languages: Vec<String>,
+    pub skills: Vec<String>,
+    pub max_estimated_hours: Option<u32>,
+}
+
+// Full-text search index entry: one per (term, bounty)
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TermKey {
+    pub term: String,
+    pub bounty_id: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone)]
+pub struct SearchPosting {
+    pub weight: u32,
+    pub status: BountyStatus,
+}
+
+// What is currently indexed for a bounty, so it can be unindexed without the old record
+#[derive(CandidType, Deserialize, Clone)]
+pub struct SearchDoc {
+    pub terms: Vec<(String, u32)>,
+    pub status: BountyStatus,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TokenAmount {
+    pub token_ledger: Principal,
+    pub amount: Nat,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RepoContributor {
+    pub principal: Principal,
+    pub bounties_won: u64,
+    pub amount_won: Vec<TokenAmount>,
+}
+
+// Aggregates over the bounties posted against a repository, or against every
+// repository of an owner when `repo` is None
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RepoStats {
+    pub owner: String,
+    pub repo: Option<String>,
+    pub bounty_count: u64,
+    pub open_count: u64,                // awaiting funding or active
+    pub completed_count: u64,           // winner selected or paid
+    pub unawarded_count: u64,           // cancelled, expired or refunded
+    pub completion_rate: Option<f64>,   // completed / (completed + unawarded); None until one has finished
+    pub value_locked: Vec<TokenAmount>, // held in escrow by active bounties and unclaimed prizes
+    pub top_contributors: Vec<RepoContributor>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct OwnerStats {
+    pub total: RepoStats,
+    pub repos: Vec<RepoStats>, // most bounties first
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SearchHit {
+    pub bounty: BountySummary,
+    pub score: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SearchPage {
+    pub hits: Vec<SearchHit>,
+    pub total_matches: u64,
+    pub next_cursor: Option<String>,
+}
+
+// Sorted listings
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BountySort {
+    CreatedAt,
+    PrizeAmount, // normalized across ledgers by token decimals
+    Deadline,    // bounties without a deadline come last in ascending order
+    Submissions,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct BountyListQuery {
+    pub sort: BountySort,
+    pub direction: SortDirection,
+    pub status: Option<BountyStatus>,
+    pub cursor: Option<String>, // next_cursor from the previous page
+    pub limit: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BountyPage {
+    pub bounties: Vec<Bounty>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BountySummaryPage {
+    pub bounties: Vec<BountySummary>,
+    pub next_cursor: Option<String>,
+}
+
+// Sort index entry: one per (sort order, bounty)
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey {
+    pub sort: BountySort,
+    pub value: u128,
+    pub bounty_id: u64,
+}
+
+// Status sort index entry: the same key, grouped by status code first so a
+// listing filtered by status reads only that status's range
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StatusSortKey {
+    pub status: u8,
+    pub key: SortKey,
+}
+
+// Open: reservations are informational only, anyone may submit.
+// Exclusive: while a reservation is live only its holder may submit.
+// Application: only applicants approved by the creator may submit.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum CompetitionMode {
+    Open,
+    Exclusive,
+    Application,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Reservation {
+    pub bounty_id: u64,
+    pub holder: Principal,
+    pub reserved_at: u64,
+    pub expires_at: u64,
+    pub renewals: u32,
+}
+
+impl Reservation {
+    pub fn is_live(&self, now: u64) -> bool {
+        now < self.expires_at
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ApplicationStatus {
+    Pending,
+    Approved,
+    Declined,
+    Withdrawn,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Application {
+    pub bounty_id: u64,
+    pub applicant: Principal,
+    pub pitch: String,
+    pub status: ApplicationStatus,
+    pub applied_at: u64,
+    pub decided_at: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct ApplyForBountyRequest {
+    pub bounty_id: u64,
+    pub pitch: String,
+}
+
+// Delegable rights on a single bounty. The creator implicitly holds all of them.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    ReviewSubmissions,
+    SelectWinner,
+    EditDetails,
+    Cancel,
+}
+
+impl Permission {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Permission::ReviewSubmissions => "review submissions",
+            Permission::SelectWinner => "select a winner",
+            Permission::EditDetails => "edit details",
+            Permission::Cancel => "cancel or refund",
+        }
+    }
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Maintainer {
+    pub bounty_id: u64,
+    pub principal: Principal,
+    pub permissions: Vec<Permission>,
+    pub added_by: Principal,
+    pub added_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct UpdateBountyRequest {
+    pub bounty_id: u64,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub deadline: Option<u64>,
+    pub labels: Option<BountyLabels>, // replaces all labels when set
+    pub pr_requirement: Option<PrRequirement>,
+}
+
+// Organization types
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrgRole {
+    Owner,
+    Admin,
+    Reviewer,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Organization {
+    pub id: u64,
+    pub handle: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub github_org: Option<String>,
+    pub avatar_url: Option<String>,
+    pub created_by: Principal,
+    pub created_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct OrgMember {
+    pub org_id: u64,
+    pub principal: Principal,
+    pub role: OrgRole,
+    pub added_by: Principal,
+    pub added_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreateOrganizationRequest {
+    pub handle: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub github_org: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct UpdateOrganizationRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub github_org: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+// Recurring bounty series
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum SeriesStatus {
+    Active,
+    Paused,
+    Cancelled,
+}
+
+// Fields copied into every bounty a series spawns
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BountyTemplate {
+    pub title: String,
+    pub description: String,
+    pub github_issue_url: String,
+    pub prize_amount: Nat,
+    pub token_ledger: Principal,
+    pub competition_mode: Option<CompetitionMode>,
+    pub max_reservation_ns: Option<u64>,
+    pub deadline_offset_ns: Option<u64>, // each bounty's deadline, relative to when it is spawned
+    pub labels: Option<BountyLabels>,
+    pub pr_requirement: Option<PrRequirement>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RecurringSeries {
+    pub id: u64,
+    pub creator: Principal,
+    pub org_id: Option<u64>,
+    pub template: BountyTemplate,
+    pub interval_ns: u64,
+    pub next_run_at: u64,
+    pub status: SeriesStatus,
+    pub budget_subaccount: [u8; 32],
+    pub spawned_count: u64,
+    pub created_at: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(CandidType, Deserialize)]
+pub struct CreateRecurringSeriesRequest {
+    pub template: BountyTemplate,
+    pub interval_ns: u64,
+    pub first_run_at: Option<u64>, // defaults to now
+    pub org_id: Option<u64>,
+}
+
+// Stored under (bounty_id, id); ids count up from 0 within each bounty
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct Submission {
+    pub id: u64,
+    pub bounty_id: u64,
+    pub submitter: Principal,
+    pub pr_url: String,
+    pub comment: String,
+    pub submitted_at: u64,
+    pub verification: Option<PrVerification>, // latest check of pr_url against GitHub
+}
+
+// What a winning PR must satisfy, checked against GitHub before select_winner succeeds
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PrRequirement {
+    Merged,                 // merged into the issue's repository
+    MergedReferencingIssue, // as Merged, and the PR title or body mentions the issue
+}
+
+// A GitHub issue in canonical form: lowercase owner and repository, no
+// trailing path, query or fragment
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GithubIssueRef {
+    pub host: String,
+    pub owner: String,
+    pub repo: String,
+    pub number: u64,
+}
+
+impl GithubIssueRef {
+    pub fn url(&self) -> String {
+        format!("https://{}/{}/{}/issues/{}", self.host, self.owner, self.repo, self.number)
+    }
+}
+
+// Secondary index entry: one per (issue, bounty). Ordered by host, owner and
+// repository first, so a repository's or owner's bounties form one range
+#[derive(CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IssueKey {
+    pub issue: GithubIssueRef,
+    pub bounty_id: u64,
+}
+
+// Where a PrVerification came from. Only checks the canister made itself are
+// trusted for payouts; webhook payloads are signed by the repository, which
+// may be the bounty creator
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum VerificationSource {
+    GithubApi,
+    Webhook,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PrVerification {
+    pub checked_at: u64,
+    pub merged: bool,
+    pub closed: Option<bool>,         // closed, merged or not; None for checks made before this was tracked
+    pub same_repo: bool,              // base repository matches the bounty's issue
+    pub references_issue: bool,
+    pub author: Option<String>,       // GitHub login of the PR author
+    pub error: Option<String>,        // set when the PR could not be checked, e.g. not found
+    pub source: Option<VerificationSource>, // None for checks made before webhooks existed, all via the API
+}
+
+impl PrVerification {
+    /// Why the PR falls short of `requirement`, if it does.
+    pub fn shortfall(&self, requirement: &PrRequirement) -> Option<String> {
+        if let Some(error) = &self.error {
+            return Some(error.clone());
+        }
+        if !self.same_repo {
+            return Some("PR is not in the bounty's repository".to_string());
+        }
+        if !self.merged {
+            return Some("PR is not merged".to_string());
+        }
+        if *requirement == PrRequirement::MergedReferencingIssue && !self.references_issue {
+            return Some("PR does not reference the bounty's issue".to_string());
+        }
+        None
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SubmissionPage {
+    pub submissions: Vec<Submission>,
+    pub next_cursor: Option<u64>, // pass as start_after to fetch the next page
+}
+
+// One entry in a bounty's status history. `from` is None for the creation entry.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StatusTransition {
+    pub bounty_id: u64,
+    pub seq: u64,
+    pub from: Option<BountyStatus>,
+    pub to: BountyStatus,
+    pub actor: Principal,
+    pub at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum EventKind {
+    BountyCreated { creator: Principal, title: String, prize_amount: Nat, token_ledger: Principal },
+    BountyFunded,
+    SubmissionAdded { submission_id: u64, submitter: Principal },
+    WinnerSelected { winner: Principal },
+    RewardClaimed { winner: Principal, amount: Nat },
+    BountyRefunded { to: Principal, amount: Nat },
+    BountyCancelled,
+    BountyExpired,
+    ProfileUpdated,
+}
+
+// One entry in the global activity log; `index` is its position in the log
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Event {
+    pub index: u64,
+    pub timestamp: u64,
+    pub actor: Principal,
+    pub bounty_id: Option<u64>,
+    pub kind: EventKind,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EventQuery {
+    pub bounty_id: Option<u64>,
+    pub principal: Option<Principal>, // events the principal performed or is named in
+    pub start: Option<u64>,           // first index to return; next_index from the previous page
+    pub descending: bool,             // newest first
+    pub limit: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    pub next_index: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum NotificationKind {
+    SubmissionReceived { submission_id: u64, submitter: Principal }, // to the creator
+    WinnerSelected,                                                   // to the winner
+    DeadlineApproaching { deadline: u64 },                            // to the creator and submitters
+    RefundAvailable { amount: Nat },                                  // to the creator of an expired bounty
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Notification {
+    pub id: u64, // increasing per recipient
+    pub bounty_id: u64,
+    pub kind: NotificationKind,
+    pub created_at: u64,
+    pub read: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct NotificationQuery {
+    pub unread_only: bool,
+    pub before: Option<u64>, // newest first; next_before from the previous page
+    pub limit: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct NotificationPage {
+    pub notifications: Vec<Notification>,
+    pub next_before: Option<u64>,
+    pub unread_count: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CertifiedEscrowAccount {
+    pub account: String, // sha256 of its UTF-8 bytes is the leaf at escrow/<id>
+    pub certificate: Vec<u8>,
+    pub witness: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CertifiedRecord {
+    pub data: Vec<u8>, // candid-encoded record; its sha256 is the leaf
+    pub certificate: Vec<u8>,
+    pub witness: Vec<u8>,
+}
+
+// HTTP gateway interface (see the IC interface spec's http_request)
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub upgrade: Option<bool>,
+}
+
+// Bounties used to embed their submissions. These shapes exist only so that
+// post_upgrade can move that data into SUBMISSIONS.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LegacySubmission {
     pub submitter: Principal,
     pub pr_url: String,
     pub comment: String,
     pub submitted_at: u64,
 }
 
+#[derive(CandidType, Deserialize)]
+pub struct EmbeddedSubmissions {
+    pub submissions: Option<Vec<LegacySubmission>>,
+}
+
 #[derive(CandidType, Deserialize)]
 pub struct CreateBountyRequest {
     pub title: String,
@@ -47,6 +660,12 @@ pub struct CreateBountyRequest {
     pub from_subaccount: Option<[u8; 32]>, // NEW: for transfer
     pub fee: Option<Nat>,                  // NEW: for transfer
     pub deadline: Option<u64>, // NEW: nanoseconds since epoch
+    pub competition_mode: Option<CompetitionMode>,
+    pub max_reservation_ns: Option<u64>, // longest window a contributor may reserve at once
+    pub org_id: Option<u64>,             // caller must be an org Owner or Admin
+    pub labels: Option<BountyLabels>,
+    pub pr_requirement: Option<PrRequirement>, // needs a github.com issue URL
+    pub stack: Option<bool>, // add to an issue that already has an open bounty instead of failing
 }
 
 #[derive(CandidType, Deserialize)]
@@ -67,6 +686,25 @@ pub struct UserProfile {
     pub bounties_posted: u64,
     pub bounties_participated: u64,
     pub bounties_won: u64,
+    pub github_verified: Option<VerifiedGithub>, // set only by the ownership proof flow
+}
+
+// A GitHub account the principal has proven control of
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct VerifiedGithub {
+    pub login: String, // lowercase
+    pub proof_url: String,
+    pub verified_at: u64,
+}
+
+// Pending proof: `token` must appear in a gist or repo file owned by `login`
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GithubChallenge {
+    pub login: String,
+    pub token: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    pub attempts: u32, // proof checks so far, carried over from an unexpired previous challenge
 }
 
 impl Default for UserProfile {
@@ -80,12 +718,13 @@ impl Default for UserProfile {
             bounties_posted: 0,
             bounties_participated: 0,
             bounties_won: 0,
+            github_verified: None,
         }
     }
 }
 
 impl Storable for UserProfile {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         match candid::encode_one(self) {
             Ok(bytes) => Cow::Owned(bytes),
             Err(_) => Cow::Owned(vec![]),
@@ -107,7 +746,7 @@ pub struct UpdateUserProfileRequest {
 
 // Implement Storable for types to use in stable structures
 impl Storable for Bounty {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         match candid::encode_one(self) {
             Ok(bytes) => Cow::Owned(bytes),
             Err(_) => Cow::Owned(vec![]),
@@ -123,11 +762,18 @@ impl Storable for Bounty {
             prize_amount: Nat::from(0u64),
             token_ledger: Principal::anonymous(),
             status: BountyStatus::PendingFunding,
-            submissions: vec![],
             winner: None,
             created_at: 0,
             escrow_subaccount: [0u8; 32],
             deadline: None,
+            competition_mode: None,
+            max_reservation_ns: None,
+            org_id: None,
+            series_id: None,
+            labels: None,
+            pr_requirement: None,
+            issue_closed_at: None,
+            funded_at: None,
         })
     }
     const BOUND: Bound = Bound::Unbounded;
@@ -138,7 +784,7 @@ impl Storable for Bounty {
 pub struct BountyIdList(pub Vec<u64>);
 
 impl Storable for BountyIdList {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         match candid::encode_one(self) {
             Ok(bytes) => Cow::Owned(bytes),
             Err(_) => Cow::Owned(vec![]),
@@ -158,7 +804,7 @@ pub struct ClaimKey {
 }
 
 impl Storable for ClaimKey {
-    fn to_bytes(&self) -> Cow<[u8]> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
         match candid::encode_one(self) {
             Ok(bytes) => Cow::Owned(bytes),
             Err(_) => Cow::Owned(vec![]),
@@ -168,4 +814,347 @@ impl Storable for ClaimKey {
         candid::decode_one(&bytes).unwrap_or_else(|_| ClaimKey { bounty_id: 0, principal: Principal::anonymous() })
     }
     const BOUND: Bound = Bound::Unbounded;
-} 
\ No newline at end of file
+} 
+impl Storable for Reservation {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| Reservation {
+            bounty_id: 0,
+            holder: Principal::anonymous(),
+            reserved_at: 0,
+            expires_at: 0,
+            renewals: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Application {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| Application {
+            bounty_id: 0,
+            applicant: Principal::anonymous(),
+            pitch: String::new(),
+            status: ApplicationStatus::Withdrawn,
+            applied_at: 0,
+            decided_at: None,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Maintainer {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| Maintainer {
+            bounty_id: 0,
+            principal: Principal::anonymous(),
+            permissions: vec![],
+            added_by: Principal::anonymous(),
+            added_at: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Organization {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| Organization {
+            id: 0,
+            handle: String::new(),
+            name: String::new(),
+            description: None,
+            website: None,
+            github_org: None,
+            avatar_url: None,
+            created_by: Principal::anonymous(),
+            created_at: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for OrgMember {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| OrgMember {
+            org_id: 0,
+            principal: Principal::anonymous(),
+            role: OrgRole::Reviewer,
+            added_by: Principal::anonymous(),
+            added_at: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for RecurringSeries {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| RecurringSeries {
+            id: 0,
+            creator: Principal::anonymous(),
+            org_id: None,
+            template: BountyTemplate {
+                title: String::new(),
+                description: String::new(),
+                github_issue_url: String::new(),
+                prize_amount: Nat::from(0u64),
+                token_ledger: Principal::anonymous(),
+                competition_mode: None,
+                max_reservation_ns: None,
+                deadline_offset_ns: None,
+                labels: None,
+                pr_requirement: None,
+            },
+            interval_ns: 0,
+            next_run_at: 0,
+            status: SeriesStatus::Cancelled,
+            budget_subaccount: [0u8; 32],
+            spawned_count: 0,
+            created_at: 0,
+            last_error: None,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for LabelKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| LabelKey { kind: LabelKind::Tag, value: String::new(), bounty_id: 0 })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for IssueKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| IssueKey {
+            issue: GithubIssueRef { host: String::new(), owner: String::new(), repo: String::new(), number: 0 },
+            bounty_id: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for TermKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| TermKey { term: String::new(), bounty_id: 0 })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SearchPosting {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or(SearchPosting { weight: 0, status: BountyStatus::PendingFunding })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SearchDoc {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| SearchDoc { terms: vec![], status: BountyStatus::PendingFunding })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for SortKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or(SortKey { sort: BountySort::CreatedAt, value: 0, bounty_id: 0 })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for StatusSortKey {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or(StatusSortKey {
+            status: 0,
+            key: SortKey { sort: BountySort::CreatedAt, value: 0, bounty_id: 0 },
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Submission {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| Submission {
+            id: 0,
+            bounty_id: 0,
+            submitter: Principal::anonymous(),
+            pr_url: String::new(),
+            comment: String::new(),
+            submitted_at: 0,
+            verification: None,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for EmbeddedSubmissions {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or(EmbeddedSubmissions { submissions: None })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for StatusTransition {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| StatusTransition {
+            bounty_id: 0,
+            seq: 0,
+            from: None,
+            to: BountyStatus::PendingFunding,
+            actor: Principal::anonymous(),
+            at: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Notification {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or(Notification {
+            id: 0,
+            bounty_id: 0,
+            kind: NotificationKind::WinnerSelected,
+            created_at: 0,
+            read: true,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for Event {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| Event {
+            index: 0,
+            timestamp: 0,
+            actor: Principal::anonymous(),
+            bounty_id: None,
+            kind: EventKind::ProfileUpdated,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Storable for GithubChallenge {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match candid::encode_one(self) {
+            Ok(bytes) => Cow::Owned(bytes),
+            Err(_) => Cow::Owned(vec![]),
+        }
+    }
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        candid::decode_one(&bytes).unwrap_or_else(|_| GithubChallenge {
+            login: String::new(),
+            token: String::new(),
+            issued_at: 0,
+            expires_at: 0,
+            attempts: 0,
+        })
+    }
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,241 @@
+// GitHub webhook deliveries, POSTed to /webhooks/github and answered from
+// http_request_update.
+//
+// Each repository registers its own secret with `set_repo_webhook_secret`; a
+// delivery is only acted on if its X-Hub-Signature-256 header is the
+// HMAC-SHA256 of the raw body under the secret of the repository it names.
+// Each X-GitHub-Delivery id is acted on once; repeats within DELIVERY_RETENTION_NS
+// are rejected, so a replayed or redelivered payload cannot apply twice.
+//
+//   pull_request  records merged/closed state on submissions linking the PR, for
+//                 display only: payouts always re-check with GitHub
+//   issues        closed/reopened flags bounties funding the issue
+//   ping          acknowledged
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use crate::github;
+use crate::http;
+use crate::indices;
+use crate::repos;
+use crate::state::{REPO_WEBHOOK_SECRETS, WEBHOOK_DELIVERIES, WEBHOOK_DELIVERY_LOG};
+use crate::submissions;
+use crate::types::{HttpRequest, HttpResponse, PrVerification, VerificationSource};
+
+const MAX_SECRET_LEN: usize = 256;
+// GitHub sends a GUID; anything much longer is not a real delivery
+const MAX_DELIVERY_ID_LEN: usize = 64;
+// Comfortably longer than the three days GitHub offers redelivery for
+const DELIVERY_RETENTION_NS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+/// "Owner/Repo" -> "owner/repo", the key secrets are stored under.
+pub fn repo_key(full_name: &str) -> Result<String, String> {
+    let (owner, repo) = github::parse_repo(full_name)?;
+    Ok(format!("{}/{}", owner, repo))
+}
+
+pub fn set_secret(repo: String, secret: String) -> Result<(), String> {
+    if secret.is_empty() || secret.len() > MAX_SECRET_LEN {
+        return Err(format!("Webhook secret must be 1 to {} bytes", MAX_SECRET_LEN));
+    }
+    REPO_WEBHOOK_SECRETS.with(|s| s.borrow_mut().insert(repo, secret));
+    Ok(())
+}
+
+pub fn remove_secret(repo: &str) -> bool {
+    REPO_WEBHOOK_SECRETS.with(|s| s.borrow_mut().remove(&repo.to_string())).is_some()
+}
+
+pub fn has_secret(repo: &str) -> bool {
+    REPO_WEBHOOK_SECRETS.with(|s| s.borrow().contains_key(&repo.to_string()))
+}
+
+pub fn handle(request: &HttpRequest) -> HttpResponse {
+    let header = |name: &str| {
+        request.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    };
+    let Some(event) = header("x-github-event") else {
+        return http::error(400, "Missing X-GitHub-Event header");
+    };
+    let Some(delivery) = header("x-github-delivery").filter(|d| !d.is_empty() && d.len() <= MAX_DELIVERY_ID_LEN) else {
+        return http::error(400, "Missing or invalid X-GitHub-Delivery header");
+    };
+    let Some(signature) = header("x-hub-signature-256") else {
+        return http::error(401, "Missing X-Hub-Signature-256 header");
+    };
+    // The repository has to be read before the signature can be checked, since
+    // it selects the secret; nothing else in the payload is trusted until then
+    let Ok(payload) = serde_json::from_slice::<Value>(&request.body) else {
+        return http::error(400, "Body is not JSON");
+    };
+    let Some((full_name, repo)) = payload["repository"]["full_name"].as_str().and_then(|r| Some((r, repo_key(r).ok()?))) else {
+        return http::error(400, "Payload has no repository");
+    };
+    let Some(secret) = REPO_WEBHOOK_SECRETS.with(|s| s.borrow().get(&repo)) else {
+        return http::error(404, "No webhook secret registered for this repository");
+    };
+    if !signature_valid(&secret, &request.body, signature) {
+        return http::error(401, "Invalid signature");
+    }
+    // Recorded only once signed, so unsigned requests cannot burn delivery ids
+    if !record_delivery(delivery, ic_cdk::api::time()) {
+        return http::error(409, "Delivery already processed");
+    }
+    let updated = match event {
+        "ping" => Ok(0),
+        "pull_request" => on_pull_request(full_name, &payload),
+        "issues" => on_issue(full_name, &payload),
+        _ => return accepted(json!({ "ignored": event })),
+    };
+    let updated = match updated {
+        Ok(updated) => updated,
+        Err(e) => return http::error(400, &e),
+    };
+    accepted(json!({ "updated": updated }))
+}
+
+fn accepted(body: Value) -> HttpResponse {
+    http::response(200, "application/json; charset=utf-8", "no-store", body.to_string().into_bytes())
+}
+
+// "sha256=<hex>" compared in constant time
+fn signature_valid(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(expected) = header.strip_prefix("sha256=").and_then(|h| hex::decode(h).ok()) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+// Returns false if `delivery` was already seen. Forgets deliveries older than
+// DELIVERY_RETENTION_NS first.
+fn record_delivery(delivery: &str, now: u64) -> bool {
+    let cutoff = now.saturating_sub(DELIVERY_RETENTION_NS);
+    let expired: Vec<((u64, u64), String)> = WEBHOOK_DELIVERY_LOG.with(|log| {
+        log.borrow().range(..(cutoff, 0)).collect()
+    });
+    for (key, expired_delivery) in expired {
+        WEBHOOK_DELIVERY_LOG.with(|log| log.borrow_mut().remove(&key));
+        WEBHOOK_DELIVERIES.with(|d| d.borrow_mut().remove(&expired_delivery));
+    }
+    if WEBHOOK_DELIVERIES.with(|d| d.borrow().contains_key(&delivery.to_string())) {
+        return false;
+    }
+    WEBHOOK_DELIVERIES.with(|d| d.borrow_mut().insert(delivery.to_string(), now));
+    WEBHOOK_DELIVERY_LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        // Messages in the same round share a timestamp
+        let seq = log.range((now, 0)..=(now, u64::MAX)).next_back().map_or(0, |((_, seq), _)| seq + 1);
+        log.insert((now, seq), delivery.to_string());
+    });
+    true
+}
+
+// Returns the number of submissions updated. The result is recorded as coming
+// from the webhook, so select_winner still asks GitHub before paying out.
+fn on_pull_request(full_name: &str, payload: &Value) -> Result<u64, String> {
+    let pull = &payload["pull_request"];
+    let Some(pr) = pull["html_url"].as_str().and_then(|url| github::parse_pull(url).ok()) else {
+        return Ok(0);
+    };
+    if !github::same_repo(full_name, &pr) {
+        return Err("Pull request is not in the repository that sent it".to_string());
+    }
+    let text = format!("{}\n{}", pull["title"].as_str().unwrap_or_default(), pull["body"].as_str().unwrap_or_default());
+    let base_repo = pull["base"]["repo"]["full_name"].as_str().unwrap_or_default();
+    let mut updated = 0;
+    let (owner, repo) = github::parse_repo(full_name)?;
+    for bounty in indices::load(repos::bounty_ids(&owner, Some(&repo))) {
+        let Ok(issue) = github::parse_issue(&bounty.github_issue_url) else {
+            continue;
+        };
+        for submission in submissions::all(bounty.id) {
+            if github::parse_pull(&submission.pr_url).as_ref() != Ok(&pr) {
+                continue;
+            }
+            let verification = PrVerification {
+                checked_at: ic_cdk::api::time(),
+                merged: pull["merged"].as_bool().unwrap_or(false),
+                closed: pull["state"].as_str().map(|state| state == "closed"),
+                same_repo: github::same_repo(base_repo, &issue),
+                references_issue: github::references_issue(&text, &issue),
+                author: pull["user"]["login"].as_str().map(str::to_string),
+                error: None,
+                source: Some(VerificationSource::Webhook),
+            };
+            submissions::set_verification(bounty.id, submission.id, verification);
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+// Returns the number of bounties flagged or unflagged
+fn on_issue(full_name: &str, payload: &Value) -> Result<u64, String> {
+    let closed_at = match payload["action"].as_str() {
+        Some("closed") => Some(ic_cdk::api::time()),
+        Some("reopened") => None,
+        _ => return Ok(0),
+    };
+    let Some(issue) = payload["issue"]["html_url"].as_str().and_then(|url| github::parse_issue(url).ok()) else {
+        return Ok(0);
+    };
+    if !github::same_repo(full_name, &issue) {
+        return Err("Issue is not in the repository that sent it".to_string());
+    }
+    let mut updated = 0;
+    for mut bounty in indices::load(indices::ids_by_issue(&issue)) {
+        if bounty.issue_closed_at.is_some() == closed_at.is_some() {
+            continue;
+        }
+        bounty.issue_closed_at = closed_at;
+        crate::save_bounty(bounty);
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signature GitHub documents for secret "It's a Secret to Everybody" and body "Hello, World!"
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    const SIGNATURE: &str = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    #[test]
+    fn accepts_valid_signature() {
+        assert!(signature_valid(SECRET, BODY, SIGNATURE));
+        assert!(signature_valid(SECRET, BODY, &SIGNATURE.to_uppercase().replace("SHA256=", "sha256=")));
+    }
+
+    #[test]
+    fn rejects_wrong_secret_or_body() {
+        assert!(!signature_valid("another secret", BODY, SIGNATURE));
+        assert!(!signature_valid(SECRET, b"Hello, World?", SIGNATURE));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let digest = SIGNATURE.strip_prefix("sha256=").unwrap();
+        assert!(!signature_valid(SECRET, BODY, digest));
+        assert!(!signature_valid(SECRET, BODY, &format!("sha1={}", digest)));
+        assert!(!signature_valid(SECRET, BODY, "sha256=not-hex"));
+        assert!(!signature_valid(SECRET, BODY, &SIGNATURE[..SIGNATURE.len() - 2]));
+        assert!(!signature_valid(SECRET, BODY, "sha256="));
+        assert!(!signature_valid(SECRET, BODY, ""));
+    }
+
+    #[test]
+    fn rejects_repeated_deliveries_until_forgotten() {
+        let now = DELIVERY_RETENTION_NS;
+        assert!(record_delivery("72d3162e-cc78-11e3-81ab-4c9367dc0958", now));
+        assert!(!record_delivery("72d3162e-cc78-11e3-81ab-4c9367dc0958", now + 1));
+        assert!(record_delivery("a8e4e3b2-cc78-11e3-81ab-4c9367dc0958", now + 1));
+        assert!(record_delivery("72d3162e-cc78-11e3-81ab-4c9367dc0958", now * 2 + 1));
+    }
+}